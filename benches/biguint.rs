@@ -0,0 +1,30 @@
+use algorithm_toolbox::biguint::BigUint;
+use algorithm_toolbox::fibonacci::fibonacci_big;
+use rand::RngCore;
+use std::time::Instant;
+
+fn bench(name: &str, n: &BigUint) {
+    let start = Instant::now();
+    let digits = n.to_string();
+    println!(
+        "{:<24} digits = {:>9}   to_string {:>10.2?}",
+        name,
+        digits.len(),
+        start.elapsed()
+    );
+}
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    for &limbs in &[1_000, 10_000, 100_000] {
+        let hex: String = (0..limbs)
+            .map(|_| format!("{:08x}", rng.next_u32()))
+            .collect();
+        let n = BigUint::from_str_radix(&hex, 16).unwrap();
+        bench(&format!("random {} limbs", limbs), &n);
+    }
+    let start = Instant::now();
+    let n = fibonacci_big(1_000_000);
+    println!("fibonacci_big(1000000) computed in {:.2?}", start.elapsed());
+    bench("fibonacci_big(1000000)", &n);
+}
@@ -0,0 +1,42 @@
+use algorithm_toolbox::sort::{quick_sort, quick_sorted};
+use rand::RngCore;
+use std::time::{Duration, Instant};
+
+fn time<F: FnMut()>(mut run: F) -> Duration {
+    let start = Instant::now();
+    run();
+    start.elapsed()
+}
+
+fn bench(name: &str, values: &[u64]) {
+    let mut in_place = values.to_vec();
+    let in_place_time = time(|| quick_sort(&mut in_place));
+    let mut allocating = Vec::new();
+    let allocating_time = time(|| allocating = quick_sorted(values));
+    assert_eq!(in_place, allocating);
+    println!(
+        "{:<24} n = {:>9}   quick_sort {:>10.2?}   quick_sorted {:>10.2?}",
+        name,
+        values.len(),
+        in_place_time,
+        allocating_time
+    );
+}
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    let n = 1_000_000;
+    let random: Vec<u64> = (0..n).map(|_| rng.next_u64()).collect();
+    bench("random", &random);
+    let few_unique: Vec<u64> = (0..n).map(|_| rng.next_u64() % 10).collect();
+    bench("few unique", &few_unique);
+    bench("all equal", &vec![42; n as usize]);
+    // quick_sorted is quadratic and recurses once per element on these
+    let n = 10_000;
+    bench("sorted", &(0..n).collect::<Vec<u64>>());
+    bench("reversed", &(0..n).rev().collect::<Vec<u64>>());
+    bench(
+        "organ pipe",
+        &(0..n / 2).chain((0..n / 2).rev()).collect::<Vec<u64>>(),
+    );
+}
@@ -0,0 +1,769 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter, LowerHex, UpperHex};
+use std::ops::{Add, Div, Mul, Rem, Sub};
+use std::str::FromStr;
+
+/// Operands with fewer limbs than this are multiplied with the schoolbook method.
+const KARATSUBA_THRESHOLD: usize = 32;
+
+/// Numbers with fewer limbs than this are converted to digits one chunk at a time.
+const TO_STR_THRESHOLD: usize = 32;
+
+/// Divisors with fewer limbs than this are cheaper to use with long division than Barrett's.
+const BARRETT_THRESHOLD: usize = 1024;
+
+/// Arbitrary precision unsigned integer, stored as little-endian `u32` limbs without
+/// trailing zero limbs (zero is the empty vector).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseBigUintError {
+    Empty,
+    InvalidDigit(char),
+}
+
+impl Display for ParseBigUintError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseBigUintError::Empty => write!(f, "cannot parse integer from empty string"),
+            ParseBigUintError::InvalidDigit(c) => write!(f, "invalid digit {:?}", c),
+        }
+    }
+}
+
+impl Error for ParseBigUintError {}
+
+fn trim(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+fn cmp_limbs(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut res = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &x) in long.iter().enumerate() {
+        let sum = x as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        res.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0 {
+        res.push(carry as u32);
+    }
+    res
+}
+
+/// `a -= b`, requires `a >= b`.
+fn sub_assign_limbs(a: &mut Vec<u32>, b: &[u32]) {
+    let mut borrow = 0i64;
+    for (i, limb) in a.iter_mut().enumerate() {
+        let diff = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        *limb = diff as u32;
+        borrow = (diff < 0) as i64;
+        if i >= b.len() && borrow == 0 {
+            break;
+        }
+    }
+    debug_assert_eq!(borrow, 0, "subtraction underflow");
+    trim(a);
+}
+
+/// `acc += x * 2^(32 * shift)`, growing `acc` as needed.
+fn add_assign_shifted(acc: &mut Vec<u32>, x: &[u32], shift: usize) {
+    if acc.len() < shift + x.len() {
+        acc.resize(shift + x.len(), 0);
+    }
+    let mut carry = 0u64;
+    let mut i = 0;
+    while i < x.len() || carry > 0 {
+        if shift + i == acc.len() {
+            acc.push(0);
+        }
+        let sum = acc[shift + i] as u64 + *x.get(i).unwrap_or(&0) as u64 + carry;
+        acc[shift + i] = sum as u32;
+        carry = sum >> 32;
+        i += 1;
+    }
+}
+
+fn schoolbook_mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut res = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let current = res[i + j] as u64 + x as u64 * y as u64 + carry;
+            res[i + j] = current as u32;
+            carry = current >> 32;
+        }
+        res[i + b.len()] = carry as u32;
+    }
+    trim(&mut res);
+    res
+}
+
+fn karatsuba_mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    if a.len().min(b.len()) < KARATSUBA_THRESHOLD {
+        return schoolbook_mul(a, b);
+    }
+    // a = a1 * B^m + a0, b = b1 * B^m + b0
+    let m = a.len().max(b.len()) / 2;
+    fn split(x: &[u32], m: usize) -> (Vec<u32>, &[u32]) {
+        let (low, high) = x.split_at(m.min(x.len()));
+        let mut low = low.to_vec();
+        trim(&mut low);
+        (low, high)
+    }
+    let (a0, a1) = split(a, m);
+    let (b0, b1) = split(b, m);
+    let z0 = karatsuba_mul(&a0, &b0);
+    let z2 = karatsuba_mul(a1, b1);
+    let mut z1 = karatsuba_mul(&add_limbs(&a0, a1), &add_limbs(&b0, b1));
+    sub_assign_limbs(&mut z1, &z0);
+    sub_assign_limbs(&mut z1, &z2);
+
+    let mut res = z0;
+    add_assign_shifted(&mut res, &z1, m);
+    add_assign_shifted(&mut res, &z2, 2 * m);
+    trim(&mut res);
+    res
+}
+
+/// Divides by a single limb, returning the quotient and the remainder.
+fn div_rem_limb(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut rem = 0u64;
+    for i in (0..a.len()).rev() {
+        let current = (rem << 32) | a[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        rem = current % divisor as u64;
+    }
+    trim(&mut quotient);
+    (quotient, rem as u32)
+}
+
+fn shl_bits(a: &[u32], bits: u32) -> Vec<u32> {
+    if bits == 0 {
+        return a.to_vec();
+    }
+    let mut res = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0u32;
+    for &x in a {
+        res.push((x << bits) | carry);
+        carry = x >> (32 - bits);
+    }
+    res.push(carry);
+    res
+}
+
+fn shr_bits(a: &[u32], bits: u32) -> Vec<u32> {
+    let mut res: Vec<u32> = if bits == 0 {
+        a.to_vec()
+    } else {
+        (0..a.len())
+            .map(|i| (a[i] >> bits) | a.get(i + 1).map_or(0, |&next| next << (32 - bits)))
+            .collect()
+    };
+    trim(&mut res);
+    res
+}
+
+/// Knuth's algorithm D, for a divisor with at least two limbs and `u >= v`.
+fn div_rem_knuth(u: &[u32], v: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    const BASE: u64 = 1 << 32;
+    let n = v.len();
+    let m = u.len() - n;
+    // normalize so the top limb of the divisor has its high bit set
+    let shift = v[n - 1].leading_zeros();
+    let vn = shl_bits(v, shift);
+    let vn = &vn[..n];
+    let mut un = shl_bits(u, shift);
+    if un.len() == u.len() {
+        un.push(0);
+    }
+    let mut quotient = vec![0u32; m + 1];
+    for j in (0..=m).rev() {
+        let numerator = ((un[j + n] as u64) << 32) | un[j + n - 1] as u64;
+        let mut qhat = numerator / vn[n - 1] as u64;
+        let mut rhat = numerator % vn[n - 1] as u64;
+        while qhat >= BASE || qhat * vn[n - 2] as u64 > ((rhat << 32) | un[j + n - 2] as u64) {
+            qhat -= 1;
+            rhat += vn[n - 1] as u64;
+            if rhat >= BASE {
+                break;
+            }
+        }
+        // un[j..=j + n] -= qhat * vn
+        let (mut borrow, mut carry) = (0i64, 0u64);
+        for i in 0..n {
+            let product = qhat * vn[i] as u64 + carry;
+            carry = product >> 32;
+            let diff = un[i + j] as i64 - borrow - (product & 0xffff_ffff) as i64;
+            un[i + j] = diff as u32;
+            borrow = (diff < 0) as i64;
+        }
+        let diff = un[j + n] as i64 - borrow - carry as i64;
+        un[j + n] = diff as u32;
+        if diff < 0 {
+            // qhat was one too large, add the divisor back
+            qhat -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = un[i + j] as u64 + vn[i] as u64 + carry;
+                un[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            un[j + n] = un[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = qhat as u32;
+    }
+    trim(&mut quotient);
+    (quotient, shr_bits(&un[..n], shift))
+}
+
+fn digit_value(c: char, radix: u32) -> Result<u32, ParseBigUintError> {
+    c.to_digit(radix).ok_or(ParseBigUintError::InvalidDigit(c))
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub fn one() -> Self {
+        BigUint { limbs: vec![1] }
+    }
+
+    fn from_limbs(mut limbs: Vec<u32>) -> Self {
+        trim(&mut limbs);
+        BigUint { limbs }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Number of significant bits, 0 for zero.
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => 32 * self.limbs.len() as u64 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs.as_slice() {
+            [] => Some(0),
+            [low] => Some(*low as u64),
+            [low, high] => Some(((*high as u64) << 32) | *low as u64),
+            _ => None,
+        }
+    }
+
+    pub fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        if cmp_limbs(&self.limbs, &other.limbs) == Ordering::Less {
+            return None;
+        }
+        let mut limbs = self.limbs.clone();
+        sub_assign_limbs(&mut limbs, &other.limbs);
+        Some(BigUint { limbs })
+    }
+
+    /// Quotient and remainder. Panics if `divisor` is zero.
+    pub fn div_rem(&self, divisor: &BigUint) -> (BigUint, BigUint) {
+        match divisor.limbs.as_slice() {
+            [] => panic!("division by zero"),
+            _ if cmp_limbs(&self.limbs, &divisor.limbs) == Ordering::Less => {
+                (BigUint::zero(), self.clone())
+            }
+            &[d] => {
+                let (quotient, rem) = div_rem_limb(&self.limbs, d);
+                (BigUint::from_limbs(quotient), BigUint::from(rem as u64))
+            }
+            v => {
+                let (quotient, rem) = div_rem_knuth(&self.limbs, v);
+                (BigUint::from_limbs(quotient), BigUint::from_limbs(rem))
+            }
+        }
+    }
+
+    /// Parses digits in any radix from 2 to 36, ignoring `_` separators.
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<BigUint, ParseBigUintError> {
+        assert!((2..=36).contains(&radix), "radix must be in 2..=36");
+        let digits: Vec<char> = s.chars().filter(|&c| c != '_').collect();
+        if digits.is_empty() {
+            return Err(ParseBigUintError::Empty);
+        }
+        // biggest power of the radix fitting in a limb, to consume several digits per step
+        let (mut chunk_size, mut chunk_base) = (1, radix as u64);
+        while chunk_base * radix as u64 <= u32::MAX as u64 {
+            chunk_size += 1;
+            chunk_base *= radix as u64;
+        }
+        let mut limbs: Vec<u32> = Vec::new();
+        for chunk in digits.chunks(chunk_size) {
+            let mut value = 0u64;
+            for &c in chunk {
+                value = value * radix as u64 + digit_value(c, radix)? as u64;
+            }
+            let scale = (radix as u64).pow(chunk.len() as u32);
+            let mut carry = value;
+            for limb in limbs.iter_mut() {
+                let current = *limb as u64 * scale + carry;
+                *limb = current as u32;
+                carry = current >> 32;
+            }
+            if carry > 0 {
+                limbs.push(carry as u32);
+            }
+        }
+        Ok(BigUint::from_limbs(limbs))
+    }
+
+    /// Lowercase digits in any radix from 2 to 36.
+    ///
+    /// Large numbers are split recursively by precomputed powers `chunk_base^(2^k)`, where a
+    /// chunk is as many digits as fit in a limb, and the halves are converted independently.
+    /// Splits by powers of at least `BARRETT_THRESHOLD` limbs are Barrett divisions, with a
+    /// reciprocal refined from the one of the power below, so the whole conversion costs
+    /// `O(M(n) log n)`, about `n^1.6` with Karatsuba multiplication, rather than `O(n^2)`.
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        assert!((2..=36).contains(&radix), "radix must be in 2..=36");
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        let (mut chunk_size, mut chunk_base) = (1, radix as u64);
+        while chunk_base * radix as u64 <= u32::MAX as u64 {
+            chunk_size += 1;
+            chunk_base *= radix as u64;
+        }
+        let chunks = ChunkFormat {
+            radix,
+            size: chunk_size,
+            base: chunk_base as u32,
+        };
+        let mut res = String::new();
+        if self.limbs.len() < TO_STR_THRESHOLD {
+            chunks.write_chunks(&self.limbs, None, &mut res);
+            return res;
+        }
+        // every power is at most self, so self is below the square of the last one
+        let mut powers = vec![SplitPower::new(BigUint::from(chunk_base))];
+        loop {
+            let last = powers.last().unwrap();
+            let square = &last.value * &last.value;
+            if &square > self {
+                break;
+            }
+            let next = last.square(square);
+            powers.push(next);
+        }
+        chunks.write(self, &powers, false, &mut res);
+        res
+    }
+}
+
+/// `1` shifted left by `shift` limbs.
+fn limb_power(shift: usize) -> BigUint {
+    let mut limbs = vec![0; shift];
+    limbs.push(1);
+    BigUint { limbs }
+}
+
+/// `n` shifted right by `shift` limbs.
+fn shr_limbs(n: &BigUint, shift: usize) -> BigUint {
+    BigUint::from_limbs(n.limbs.get(shift..).unwrap_or(&[]).to_vec())
+}
+
+/// Divisor of `to_str_radix`. From `BARRETT_THRESHOLD` limbs on it carries the reciprocal
+/// `floor(2^(64 * len) / value)`, where `len` is its number of limbs, for Barrett division.
+struct SplitPower {
+    value: BigUint,
+    reciprocal: Option<BigUint>,
+}
+
+impl SplitPower {
+    fn new(value: BigUint) -> Self {
+        SplitPower {
+            value,
+            reciprocal: None,
+        }
+    }
+
+    /// `square = self.value^2`, with its reciprocal refined from the square of this one.
+    fn square(&self, square: BigUint) -> Self {
+        let (len, half) = (square.limbs.len(), self.value.limbs.len());
+        let scale = limb_power(2 * len);
+        let reciprocal = match &self.reciprocal {
+            _ if len < BARRETT_THRESHOLD => None,
+            None => Some(scale.div_rem(&square).0),
+            Some(reciprocal) => {
+                // both steps stay below the exact reciprocal: squaring loses about half of the
+                // correct limbs and one Newton step doubles them again
+                let estimate = shr_limbs(&(reciprocal * reciprocal), 4 * half - 2 * len);
+                let error = &scale - &(&square * &estimate);
+                let estimate = &estimate + &shr_limbs(&(&estimate * &error), 2 * len);
+                // the remaining error is a couple of limbs, fixed with a short division
+                let error = &scale - &(&square * &estimate);
+                Some(&estimate + &(&error / &square))
+            }
+        };
+        SplitPower {
+            value: square,
+            reciprocal,
+        }
+    }
+
+    /// Quotient and remainder of `n < value^2`.
+    fn div_rem(&self, n: &BigUint) -> (BigUint, BigUint) {
+        let reciprocal = match &self.reciprocal {
+            Some(reciprocal) => reciprocal,
+            None => return n.div_rem(&self.value),
+        };
+        let len = self.value.limbs.len();
+        // at most 2 below the quotient
+        let mut quotient = shr_limbs(&(n * reciprocal), 2 * len);
+        let mut rem = n - &(&quotient * &self.value);
+        while rem >= self.value {
+            rem = &rem - &self.value;
+            quotient = &quotient + &BigUint::one();
+        }
+        (quotient, rem)
+    }
+}
+
+/// How `to_str_radix` packs digits in a limb.
+struct ChunkFormat {
+    radix: u32,
+    size: usize,
+    base: u32,
+}
+
+impl ChunkFormat {
+    /// Appends the digits of `n < base^(2^powers.len())`, where `powers[k] = base^(2^k)`.
+    /// With `pad` the digits are zero padded to that full width.
+    fn write(&self, n: &BigUint, powers: &[SplitPower], pad: bool, res: &mut String) {
+        match powers.split_last() {
+            // without padding a leading zero half would turn into padded zeros, so skip it
+            Some((power, lower)) if !pad && n < &power.value => self.write(n, lower, false, res),
+            Some((power, lower)) if n.limbs.len() >= TO_STR_THRESHOLD => {
+                let (high, low) = power.div_rem(n);
+                self.write(&high, lower, pad, res);
+                self.write(&low, lower, true, res);
+            }
+            _ => {
+                let width = pad.then_some(self.size << powers.len());
+                self.write_chunks(&n.limbs, width, res);
+            }
+        }
+    }
+
+    /// Appends the digits of `limbs` one chunk at a time, zero padded to `width` if given.
+    fn write_chunks(&self, limbs: &[u32], width: Option<usize>, res: &mut String) {
+        let mut chunks: Vec<u32> = Vec::new();
+        let mut current = limbs.to_vec();
+        while !current.is_empty() {
+            let (quotient, rem) = div_rem_limb(&current, self.base);
+            chunks.push(rem);
+            current = quotient;
+        }
+        let mut digits = Vec::with_capacity(self.size * chunks.len());
+        for (i, &chunk) in chunks.iter().enumerate() {
+            let mut chunk = chunk;
+            let start = digits.len();
+            while chunk > 0 {
+                digits.push(std::char::from_digit(chunk % self.radix, self.radix).unwrap());
+                chunk /= self.radix;
+            }
+            // all chunks but the most significant one are zero padded
+            if i + 1 < chunks.len() {
+                digits.resize(start + self.size, '0');
+            }
+        }
+        if let Some(width) = width {
+            digits.resize(width, '0');
+        }
+        res.extend(digits.iter().rev());
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        BigUint::from_limbs(vec![value as u32, (value >> 32) as u32])
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(value: u128) -> Self {
+        BigUint::from_limbs((0..4).map(|i| (value >> (32 * i)) as u32).collect())
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = ParseBigUintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BigUint::from_str_radix(s, 10)
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "", &self.to_str_radix(10))
+    }
+}
+
+impl LowerHex for BigUint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_str_radix(16))
+    }
+}
+
+impl UpperHex for BigUint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_str_radix(16).to_uppercase())
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_limbs(&self.limbs, &other.limbs)
+    }
+}
+
+impl<'a> Add<&'a BigUint> for &'a BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: &BigUint) -> BigUint {
+        BigUint::from_limbs(add_limbs(&self.limbs, &rhs.limbs))
+    }
+}
+
+impl<'a> Sub<&'a BigUint> for &'a BigUint {
+    type Output = BigUint;
+
+    /// Panics if `rhs > self`.
+    fn sub(self, rhs: &BigUint) -> BigUint {
+        self.checked_sub(rhs).expect("subtraction underflow")
+    }
+}
+
+impl<'a> Mul<&'a BigUint> for &'a BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: &BigUint) -> BigUint {
+        BigUint::from_limbs(karatsuba_mul(&self.limbs, &rhs.limbs))
+    }
+}
+
+impl<'a> Div<&'a BigUint> for &'a BigUint {
+    type Output = BigUint;
+
+    fn div(self, rhs: &BigUint) -> BigUint {
+        self.div_rem(rhs).0
+    }
+}
+
+impl<'a> Rem<&'a BigUint> for &'a BigUint {
+    type Output = BigUint;
+
+    fn rem(self, rhs: &BigUint) -> BigUint {
+        self.div_rem(rhs).1
+    }
+}
+
+macro_rules! forward_owned_ops {
+    ($($trait:ident $method:ident),*) => {
+        $(impl $trait<BigUint> for BigUint {
+            type Output = BigUint;
+
+            fn $method(self, rhs: BigUint) -> BigUint {
+                (&self).$method(&rhs)
+            }
+        }
+
+        impl<'a> $trait<&'a BigUint> for BigUint {
+            type Output = BigUint;
+
+            fn $method(self, rhs: &BigUint) -> BigUint {
+                (&self).$method(rhs)
+            }
+        })*
+    };
+}
+
+forward_owned_ops!(Add add, Sub sub, Mul mul, Div div, Rem rem);
+
+#[cfg(test)]
+mod test {
+    use crate::biguint::{karatsuba_mul, schoolbook_mul, BigUint, ParseBigUintError};
+    use rand::RngCore;
+
+    fn random_biguint(rng: &mut impl RngCore, limbs: usize) -> BigUint {
+        BigUint::from_limbs((0..limbs).map(|_| rng.next_u32()).collect())
+    }
+
+    #[test]
+    fn arithmetic_matches_u128() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let a = rng.next_u64() as u128;
+            let b = (rng.next_u64() >> (rng.next_u32() % 64)) as u128;
+            let (x, y) = (BigUint::from(a), BigUint::from(b));
+            assert_eq!(&x + &y, BigUint::from(a + b));
+            assert_eq!(&x * &y, BigUint::from(a * b));
+            if a >= b {
+                assert_eq!(&x - &y, BigUint::from(a - b));
+            } else {
+                assert_eq!(x.checked_sub(&y), None);
+            }
+            if let Some(q) = a.checked_div(b) {
+                assert_eq!(x.div_rem(&y), (BigUint::from(q), BigUint::from(a % b)));
+            }
+        }
+    }
+
+    #[test]
+    fn karatsuba_matches_schoolbook() {
+        let mut rng = rand::thread_rng();
+        for &(n, m) in &[(32, 32), (100, 70), (250, 33), (64, 1000)] {
+            let a = random_biguint(&mut rng, n);
+            let b = random_biguint(&mut rng, m);
+            assert_eq!(
+                karatsuba_mul(&a.limbs, &b.limbs),
+                schoolbook_mul(&a.limbs, &b.limbs)
+            );
+        }
+    }
+
+    #[test]
+    fn div_rem_big() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let n = 1 + rng.next_u32() as usize % 80;
+            let m = 1 + rng.next_u32() as usize % 40;
+            let a = random_biguint(&mut rng, n);
+            let b = random_biguint(&mut rng, m);
+            if b.is_zero() {
+                continue;
+            }
+            let (q, r) = a.div_rem(&b);
+            assert!(r < b);
+            assert_eq!(&(&q * &b) + &r, a);
+        }
+    }
+
+    #[test]
+    fn div_rem_add_back_case() {
+        // forces the rare correction step of algorithm D
+        let a = BigUint::from_str_radix("800000000000000000000003", 16).unwrap();
+        let b = BigUint::from_str_radix("200000000000000000000001", 16).unwrap();
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(q, BigUint::from(3u64));
+        assert_eq!(
+            r,
+            BigUint::from_str_radix("200000000000000000000000", 16).unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn division_by_zero() {
+        let _ = BigUint::one() / BigUint::zero();
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let s = "123456789012345678901234567890123456789012345678901234567890";
+        let n: BigUint = s.parse().unwrap();
+        assert_eq!(n.to_string(), s);
+        assert_eq!(format!("{:x}", BigUint::from(0xdead_beefu64)), "deadbeef");
+        assert_eq!(
+            format!("{:#X}", BigUint::from(0xdead_beefu64)),
+            "0xDEADBEEF"
+        );
+        assert_eq!(
+            BigUint::from_str_radix("ff_ffff_ffff_ffff_ffff", 16),
+            Ok(BigUint::from((1u128 << 72) - 1))
+        );
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(format!("{:>5}", BigUint::from(42u64)), "   42");
+        assert_eq!(BigUint::from(1_000_000_000u64).to_string(), "1000000000");
+        assert_eq!("".parse::<BigUint>(), Err(ParseBigUintError::Empty));
+        assert_eq!(
+            "12a".parse::<BigUint>(),
+            Err(ParseBigUintError::InvalidDigit('a'))
+        );
+    }
+
+    #[test]
+    fn parse_display_round_trip() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let limbs = 1 + rng.next_u32() as usize % 40;
+            let n = random_biguint(&mut rng, limbs);
+            assert_eq!(n.to_string().parse::<BigUint>(), Ok(n.clone()));
+            assert_eq!(BigUint::from_str_radix(&format!("{:x}", n), 16), Ok(n));
+        }
+    }
+
+    #[test]
+    fn to_str_radix_large() {
+        let mut rng = rand::thread_rng();
+        for &radix in &[2, 3, 10, 16, 36] {
+            for &limbs in &[31, 32, 100, 257] {
+                let n = random_biguint(&mut rng, limbs);
+                assert_eq!(
+                    BigUint::from_str_radix(&n.to_str_radix(radix), radix),
+                    Ok(n)
+                );
+            }
+        }
+        // the low halves are zero or all nines, so padding decides every digit
+        let power = BigUint::from_str_radix(&format!("1{}", "0".repeat(5000)), 10).unwrap();
+        assert_eq!(power.to_string(), format!("1{}", "0".repeat(5000)));
+        let below = power.checked_sub(&BigUint::one()).unwrap();
+        assert_eq!(below.to_string(), "9".repeat(5000));
+    }
+
+    #[test]
+    fn to_str_radix_barrett() {
+        // large enough for the top splits to go through Barrett division
+        let n = random_biguint(&mut rand::thread_rng(), 5_000);
+        assert_eq!(n.to_string().parse::<BigUint>(), Ok(n.clone()));
+        assert_eq!(BigUint::from_str_radix(&n.to_str_radix(7), 7), Ok(n));
+        let digits = 25_000;
+        let power = BigUint::from_str_radix(&format!("1{}", "0".repeat(digits)), 10).unwrap();
+        assert_eq!(power.to_string(), format!("1{}", "0".repeat(digits)));
+        let below = power.checked_sub(&BigUint::one()).unwrap();
+        assert_eq!(below.to_string(), "9".repeat(digits));
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(BigUint::from(u64::MAX).to_u64(), Some(u64::MAX));
+        assert_eq!(BigUint::from(u128::MAX).to_u64(), None);
+        assert_eq!(BigUint::from(u128::MAX).bits(), 128);
+        assert_eq!(BigUint::zero().bits(), 0);
+        assert!(BigUint::from(0u64).is_zero());
+    }
+}
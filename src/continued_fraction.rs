@@ -0,0 +1,352 @@
+use crate::biguint::BigUint;
+use crate::nums::{isqrt, isqrt_u128};
+use crate::ratio::Ratio;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Continued fraction `[prefix; (period)]`, the period repeating forever. An empty period
+/// means a finite expansion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeriodicContinuedFraction {
+    pub prefix: Vec<i64>,
+    pub period: Vec<i64>,
+}
+
+impl PeriodicContinuedFraction {
+    /// All the terms in order, infinitely many unless the period is empty.
+    pub fn terms(&self) -> impl Iterator<Item = i64> + '_ {
+        self.prefix
+            .iter()
+            .chain(self.period.iter().cycle())
+            .cloned()
+    }
+}
+
+fn floor_div(a: i128, b: i128) -> i128 {
+    let q = a / b;
+    if (a % b != 0) && ((a < 0) != (b < 0)) {
+        q - 1
+    } else {
+        q
+    }
+}
+
+/// Finite continued fraction `[a0; a1, ..., an]` of a rational, with `a0` possibly negative,
+/// every other term positive and the last term greater than 1 (except for integers).
+pub fn rational_expansion(value: Ratio<i64>) -> Vec<i64> {
+    let (mut p, mut q) = (value.numer() as i128, value.denom() as i128);
+    let mut terms = Vec::new();
+    while q != 0 {
+        let a = floor_div(p, q);
+        terms.push(a as i64);
+        let r = p - a * q;
+        p = q;
+        q = r;
+    }
+    terms
+}
+
+/// Expansion of the quadratic irrational `(p + sqrt(d)) / q`, which is eventually periodic.
+/// If `d` is a perfect square the value is rational and the period is empty.
+/// When `q` does not divide `d - p^2` all three are scaled by `|q|` and the recurrence runs in
+/// `i128`, so `None` is returned if it overflows there or a term does not fit in an `i64`.
+/// The period can grow with the square root of the scaled radicand, so huge `d * q^2` are slow.
+/// Panics if `q` is 0 or `d` is negative.
+pub fn quadratic_expansion(p: i64, d: i64, q: i64) -> Option<PeriodicContinuedFraction> {
+    assert!(q != 0, "zero denominator");
+    assert!(d >= 0, "square root of a negative number");
+    let root = isqrt(d as u64) as i64;
+    if root * root == d {
+        return Some(PeriodicContinuedFraction {
+            prefix: rational_expansion(Ratio::new(p.checked_add(root)?, q)),
+            period: Vec::new(),
+        });
+    }
+    let (mut p, mut d, mut q) = (p as i128, d as i128, q as i128);
+    // the recurrence needs q | d - p^2, scaling everything by |q| ensures it
+    let mut previous_q = if (d - p * p) % q != 0 {
+        let scale = q.abs();
+        let previous_q = (d - p * p) * q.signum();
+        p *= scale;
+        d = d.checked_mul(scale)?.checked_mul(scale)?;
+        q *= scale;
+        previous_q
+    } else {
+        (d - p * p) / q
+    };
+    let root = isqrt_u128(d as u128) as i128;
+    let mut seen: HashMap<(i128, i128), usize> = HashMap::new();
+    let mut terms: Vec<i64> = Vec::new();
+    loop {
+        if let Some(&start) = seen.get(&(p, q)) {
+            let period = terms.split_off(start);
+            return Some(PeriodicContinuedFraction {
+                prefix: terms,
+                period,
+            });
+        }
+        seen.insert((p, q), terms.len());
+        // floor((p + sqrt(d)) / q), sqrt(d) being irrational
+        let a = if q > 0 {
+            floor_div(p.checked_add(root)?, q)
+        } else {
+            -floor_div(p.checked_add(root)?, -q) - 1
+        };
+        terms.push(i64::try_from(a).ok()?);
+        let next_p = a.checked_mul(q)?.checked_sub(p)?;
+        // (d - next_p^2) / q without the square, which can leave i128 before the period starts
+        let next_q = a
+            .checked_mul(p.checked_sub(next_p)?)?
+            .checked_add(previous_q)?;
+        previous_q = q;
+        p = next_p;
+        q = next_q;
+    }
+}
+
+/// Expansion of `sqrt(n)`. Panics if `n` does not fit in an `i64`.
+pub fn sqrt_expansion(n: u64) -> PeriodicContinuedFraction {
+    let n = i64::try_from(n).expect("n fits in i64");
+    quadratic_expansion(0, n, 1).expect("terms of a square root fit in i64")
+}
+
+/// Convergents `h_n / k_n` of a continued fraction, ending when they stop fitting in `i64`.
+pub struct Convergents<I> {
+    terms: I,
+    previous: (i128, i128),
+    current: (i128, i128),
+}
+
+impl<I: Iterator<Item = i64>> Iterator for Convergents<I> {
+    type Item = Ratio<i64>;
+
+    fn next(&mut self) -> Option<Ratio<i64>> {
+        let a = self.terms.next()? as i128;
+        let (h, k) = self.current;
+        let (h_prev, k_prev) = self.previous;
+        let next = (
+            a.checked_mul(h)?.checked_add(h_prev)?,
+            a.checked_mul(k)?.checked_add(k_prev)?,
+        );
+        self.previous = self.current;
+        self.current = next;
+        Some(Ratio::new(
+            i64::try_from(next.0).ok()?,
+            i64::try_from(next.1).ok()?,
+        ))
+    }
+}
+
+pub fn convergents<I: IntoIterator<Item = i64>>(terms: I) -> Convergents<I::IntoIter> {
+    Convergents {
+        terms: terms.into_iter(),
+        previous: (0, 1),
+        current: (1, 0),
+    }
+}
+
+/// Compares `|a/b - x|` with `|c/d - x|` exactly, where `x = n / m`.
+fn cmp_distance(a: u128, b: u128, c: u128, d: u128, n: u128, m: u128) -> Ordering {
+    let (n, m) = (BigUint::from(n), BigUint::from(m));
+    // |a/b - n/m| = |a*m - b*n| / (b*m)
+    let distance_numer = |p: u128, q: u128| {
+        let (pm, qn) = (&BigUint::from(p) * &m, &BigUint::from(q) * &n);
+        if pm > qn {
+            &pm - &qn
+        } else {
+            &qn - &pm
+        }
+    };
+    let left = &distance_numer(a, b) * &BigUint::from(d);
+    let right = &distance_numer(c, d) * &BigUint::from(b);
+    left.cmp(&right)
+}
+
+/// Closest fraction to `x` with a denominator of at most `max_denominator`, descending the
+/// Stern-Brocot tree along the continued fraction of the exact value of `x`. Ties are broken
+/// in favour of the smaller denominator. `None` if `x` is not finite or does not fit in `i64`.
+/// Panics if `max_denominator` is 0.
+pub fn best_approximation(x: f64, max_denominator: u64) -> Option<Ratio<i64>> {
+    assert!(max_denominator > 0, "max_denominator must be positive");
+    if !x.is_finite() || x.abs() >= 2f64.powi(63) {
+        return None;
+    }
+    if x < 0.0 {
+        return best_approximation(-x, max_denominator).map(|r| Ratio::new(-r.numer(), r.denom()));
+    }
+    let max_denominator = max_denominator.min(i64::MAX as u64) as u128;
+    let exact = match Ratio::<i128>::from_f64(x) {
+        Some(exact) => exact,
+        // below 2^-126, far closer to 0 than to 1 / max_denominator
+        None => return Some(Ratio::from_integer(0)),
+    };
+    let (numer, denom) = (exact.numer() as u128, exact.denom() as u128);
+    let fits = |p: u128, q: u128| Some(Ratio::new(i64::try_from(p).ok()?, q as i64));
+    if denom <= max_denominator {
+        return fits(numer, denom);
+    }
+    // (p0 / q0, p1 / q1) are the last two convergents with q1 <= max_denominator
+    let (mut p0, mut q0, mut p1, mut q1) = (0u128, 1u128, 1u128, 0u128);
+    let (mut n, mut d) = (numer, denom);
+    while d != 0 {
+        let a = n / d;
+        let q2 = match a.checked_mul(q1).and_then(|aq| aq.checked_add(q0)) {
+            Some(q2) if q2 <= max_denominator => q2,
+            _ => break,
+        };
+        let p2 = a * p1 + p0;
+        p0 = p1;
+        q0 = q1;
+        p1 = p2;
+        q1 = q2;
+        let r = n - a * d;
+        n = d;
+        d = r;
+    }
+    // the best semiconvergent between the two
+    let k = (max_denominator - q0) / q1;
+    let (p_semi, q_semi) = (p0 + k * p1, q0 + k * q1);
+    match cmp_distance(p1, q1, p_semi, q_semi, numer, denom) {
+        Ordering::Greater => fits(p_semi, q_semi),
+        _ => fits(p1, q1),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::continued_fraction::{
+        best_approximation, convergents, quadratic_expansion, rational_expansion, sqrt_expansion,
+        PeriodicContinuedFraction,
+    };
+    use crate::ratio::Ratio;
+    use rand::RngCore;
+    use std::f64::consts::PI;
+
+    fn periodic(prefix: &[i64], period: &[i64]) -> PeriodicContinuedFraction {
+        PeriodicContinuedFraction {
+            prefix: prefix.to_vec(),
+            period: period.to_vec(),
+        }
+    }
+
+    #[test]
+    fn rational_expansion_example() {
+        assert_eq!(rational_expansion(Ratio::new(415, 93)), vec![4, 2, 6, 7]);
+        assert_eq!(rational_expansion(Ratio::new(-7, 3)), vec![-3, 1, 2]);
+        assert_eq!(rational_expansion(Ratio::from(5)), vec![5]);
+        assert_eq!(rational_expansion(Ratio::new(1, 3)), vec![0, 3]);
+    }
+
+    #[test]
+    fn rational_expansion_round_trip() {
+        for numer in -50..50i64 {
+            for denom in 1..30i64 {
+                let r = Ratio::new(numer, denom);
+                let last = convergents(rational_expansion(r)).last().unwrap();
+                assert_eq!(last, r);
+            }
+        }
+    }
+
+    #[test]
+    fn quadratic_expansion_example() {
+        assert_eq!(sqrt_expansion(2), periodic(&[1], &[2]));
+        assert_eq!(sqrt_expansion(7), periodic(&[2], &[1, 1, 1, 4]));
+        assert_eq!(
+            sqrt_expansion(94),
+            periodic(&[9], &[1, 2, 3, 1, 1, 5, 1, 8, 1, 5, 1, 1, 3, 2, 1, 18])
+        );
+        assert_eq!(sqrt_expansion(16), periodic(&[4], &[]));
+        assert_eq!(quadratic_expansion(1, 5, 2), Some(periodic(&[], &[1])));
+        assert_eq!(quadratic_expansion(-1, 5, 2), Some(periodic(&[0], &[1])));
+        assert_eq!(quadratic_expansion(3, 9, 4), Some(periodic(&[1, 2], &[])));
+    }
+
+    #[test]
+    fn quadratic_expansion_large_denominator() {
+        // scaling by q puts the radicand near 8.1e37, well beyond u64
+        let q = 3_000_000_000i64;
+        assert_eq!(
+            quadratic_expansion(2, q * q + 1, q),
+            Some(periodic(&[], &[1, 1_499_999_999, 1, 7, 1_499_999_999, 1]))
+        );
+        assert_eq!(
+            quadratic_expansion(2, q * q + 1, -q),
+            Some(periodic(
+                &[-2, 1, 1_499_999_998],
+                &[1, 7, 1_499_999_999, 1, 1, 1_499_999_999]
+            ))
+        );
+        // a term of the period is about 1.8e19
+        assert_eq!(quadratic_expansion(0, q * q - 1, q), None);
+        assert_eq!(quadratic_expansion(1, i64::MAX, i64::MAX), None);
+    }
+
+    #[test]
+    fn quadratic_expansion_converges() {
+        for &(p, d, q) in &[(1i64, 3i64, 4i64), (-2, 10, 3), (5, 7, -2), (0, 13, -6)] {
+            let expected = (p as f64 + (d as f64).sqrt()) / q as f64;
+            let expansion = quadratic_expansion(p, d, q).unwrap();
+            let approximation = convergents(expansion.terms()).take(60).last().unwrap();
+            assert!(
+                (approximation.to_f64() - expected).abs() < 1e-12,
+                "{:?}",
+                (p, d, q)
+            );
+        }
+    }
+
+    #[test]
+    fn convergents_example() {
+        let found: Vec<Ratio<i64>> = convergents(sqrt_expansion(2).terms()).take(4).collect();
+        assert_eq!(
+            found,
+            vec![
+                Ratio::from(1),
+                Ratio::new(3, 2),
+                Ratio::new(7, 5),
+                Ratio::new(17, 12)
+            ]
+        );
+        // the golden ratio convergents are quotients of Fibonacci numbers, ending on overflow
+        let golden: Vec<Ratio<i64>> =
+            convergents(quadratic_expansion(1, 5, 2).unwrap().terms()).collect();
+        assert_eq!(golden.len(), 91);
+        assert_eq!(golden[10], Ratio::new(144, 89));
+    }
+
+    #[test]
+    fn best_approximation_example() {
+        assert_eq!(best_approximation(PI, 1000), Some(Ratio::new(355, 113)));
+        assert_eq!(best_approximation(PI, 100), Some(Ratio::new(311, 99)));
+        assert_eq!(best_approximation(PI, 7), Some(Ratio::new(22, 7)));
+        assert_eq!(best_approximation(-PI, 1000), Some(Ratio::new(-355, 113)));
+        assert_eq!(best_approximation(0.333, 10), Some(Ratio::new(1, 3)));
+        assert_eq!(best_approximation(0.75, 100), Some(Ratio::new(3, 4)));
+        assert_eq!(best_approximation(2.0, 1), Some(Ratio::from(2)));
+        assert_eq!(best_approximation(1e-30, u64::MAX), Some(Ratio::from(0)));
+        assert_eq!(best_approximation(1e30, 10), None);
+        assert_eq!(best_approximation(f64::NAN, 10), None);
+    }
+
+    #[test]
+    fn best_approximation_brute_force() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..500 {
+            let x = (rng.next_u32() % 1_000_000) as f64 / 97_331.0;
+            let max_denominator = 1 + rng.next_u64() % 60;
+            let best = best_approximation(x, max_denominator).unwrap();
+            let distance = |r: Ratio<i64>| (r.to_f64() - x).abs();
+            for q in 1..=max_denominator as i64 {
+                let p = (x * q as f64).round() as i64;
+                assert!(
+                    distance(best) <= distance(Ratio::new(p, q)) + 1e-15,
+                    "{} {}",
+                    x,
+                    q
+                );
+            }
+        }
+    }
+}
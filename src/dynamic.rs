@@ -89,7 +89,7 @@ pub fn longest_common_consecutive_subsequence(sequences: &[&[usize]]) -> usize {
     for i in (2..min_len).rev() {
         let collides = sequences
             .iter()
-            .map(|seq| HashSet::from(seq.windows(i).collect()))
+            .map(|seq| seq.windows(i).collect::<HashSet<_>>())
             .reduce(|s1, s2| s1.intersection(&s2).cloned().collect::<HashSet<&[usize]>>())
             .unwrap();
         if collides.len() != 0 {
@@ -0,0 +1,468 @@
+use crate::modint::{add_mod, Montgomery};
+use crate::nums::{gcd, lcm};
+use crate::primes::{is_prime, LinearSieve};
+
+/// Factors below this bound are found by trial division before falling back to Pollard's rho.
+const TRIAL_DIVISION_LIMIT: u64 = 1 << 10;
+/// Amount of steps whose differences are multiplied together before taking a gcd.
+const BRENT_BATCH: u64 = 128;
+
+/// Pollard's rho with Brent's cycle finding on `x^2 + c`, for an odd composite `n`.
+/// Returns a non trivial factor, or `None` if this `c` only found `n` itself.
+fn pollard_brent(n: u64, c: u64) -> Option<u64> {
+    let montgomery = Montgomery::new(n);
+    let c = montgomery.to_montgomery(c);
+    let step = |x: u64| add_mod(montgomery.mul(x, x), c, n);
+
+    let (mut x, mut y, mut y_saved) = (0, montgomery.to_montgomery(2), 0);
+    let (mut product, mut g, mut r) = (montgomery.one(), 1, 1);
+    while g == 1 {
+        x = y;
+        for _ in 0..r {
+            y = step(y);
+        }
+        let mut k = 0;
+        while k < r && g == 1 {
+            y_saved = y;
+            for _ in 0..BRENT_BATCH.min(r - k) {
+                y = step(y);
+                product = montgomery.mul(product, x.abs_diff(y));
+            }
+            // montgomery form only scales by a unit, so the gcd is unchanged
+            g = gcd(product, n);
+            k += BRENT_BATCH;
+        }
+        r *= 2;
+    }
+    if g == n {
+        // the batch overshot, replay it one step at a time
+        loop {
+            y_saved = step(y_saved);
+            g = gcd(x.abs_diff(y_saved), n);
+            if g > 1 {
+                break;
+            }
+        }
+    }
+    if g == n {
+        None
+    } else {
+        Some(g)
+    }
+}
+
+fn collect_prime_factors(n: u64, factors: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime(n) {
+        factors.push(n);
+        return;
+    }
+    let d = (1..)
+        .find_map(|c| pollard_brent(n, c))
+        .expect("every odd composite has a factor found by some c");
+    collect_prime_factors(d, factors);
+    collect_prime_factors(n / d, factors);
+}
+
+/// Prime factors of `n` with their multiplicities, in increasing order.
+/// Both `factorize(0)` and `factorize(1)` are empty.
+pub fn factorize(mut n: u64) -> Vec<(u64, u32)> {
+    let mut primes: Vec<u64> = Vec::new();
+    if n == 0 {
+        return Vec::new();
+    }
+    let trial_divisors = std::iter::once(2).chain((3..TRIAL_DIVISION_LIMIT).step_by(2));
+    for d in trial_divisors {
+        if d * d > n {
+            break;
+        }
+        while n.is_multiple_of(d) {
+            primes.push(d);
+            n /= d;
+        }
+    }
+    collect_prime_factors(n, &mut primes);
+    primes.sort_unstable();
+
+    let mut res: Vec<(u64, u32)> = Vec::new();
+    for p in primes {
+        match res.last_mut() {
+            Some((q, e)) if *q == p => *e += 1,
+            _ => res.push((p, 1)),
+        }
+    }
+    res
+}
+
+/// All divisors of `n` in increasing order, empty for 0.
+pub fn divisors(n: u64) -> Vec<u64> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut res = vec![1];
+    for (p, e) in factorize(n) {
+        let current = res.len();
+        let mut power = 1;
+        for _ in 0..e {
+            power *= p;
+            for i in 0..current {
+                res.push(res[i] * power);
+            }
+        }
+    }
+    res.sort_unstable();
+    res
+}
+
+pub fn num_divisors(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    factorize(n).iter().map(|&(_, e)| e as u64 + 1).product()
+}
+
+/// Sum of the divisors of `n`, which can exceed `u64::MAX`.
+pub fn sum_divisors(n: u64) -> u128 {
+    if n == 0 {
+        return 0;
+    }
+    factorize(n)
+        .iter()
+        .map(|&(p, e)| {
+            let p = p as u128;
+            (0..e)
+                .fold((1u128, 1u128), |(sum, power), _| {
+                    (sum + power * p, power * p)
+                })
+                .0
+        })
+        .product()
+}
+
+fn phi_prime_power(p: u64, e: u32) -> u64 {
+    p.pow(e - 1) * (p - 1)
+}
+
+fn mobius_prime_power(_: u64, e: u32) -> i8 {
+    if e > 1 {
+        0
+    } else {
+        -1
+    }
+}
+
+fn carmichael_prime_power(p: u64, e: u32) -> u64 {
+    match (p, e) {
+        (2, e) if e >= 3 => 1 << (e - 2),
+        (p, e) => p.pow(e - 1) * (p - 1),
+    }
+}
+
+fn sigma_k_prime_power(p: u64, e: u32, k: u32) -> Option<u128> {
+    let pk = (p as u128).checked_pow(k)?;
+    let (mut sum, mut power) = (1u128, 1u128);
+    for _ in 0..e {
+        power = power.checked_mul(pk)?;
+        sum = sum.checked_add(power)?;
+    }
+    Some(sum)
+}
+
+fn checked_product(a: Option<u128>, b: Option<u128>) -> Option<u128> {
+    a?.checked_mul(b?)
+}
+
+/// Value on `n >= 1` of a function known on prime powers, joining the values on the prime
+/// powers of `n` with `combine`, starting from `one`, the value on 1.
+fn evaluate<T>(
+    n: u64,
+    one: T,
+    prime_power: impl Fn(u64, u32) -> T,
+    combine: impl Fn(T, T) -> T,
+) -> T {
+    factorize(n)
+        .into_iter()
+        .fold(one, |acc, (p, e)| combine(acc, prime_power(p, e)))
+}
+
+/// Same function on every number of `0..=n` in linear time, index 0 holding `zero`.
+/// Each `m` is split as `p^e * rest` with `p` its smallest prime factor, so the value on `rest`
+/// is already in the table.
+fn tabulate<T: Copy>(
+    n: u32,
+    zero: T,
+    one: T,
+    prime_power: impl Fn(u64, u32) -> T,
+    combine: impl Fn(T, T) -> T,
+) -> Vec<T> {
+    let sieve = LinearSieve::new(n);
+    let len = n as usize + 1;
+    let mut table = vec![zero; len];
+    // the rest and the exponent of the smallest prime factor of each number
+    let (mut rest, mut exponent) = (vec![1usize; len], vec![0u32; len]);
+    if n >= 1 {
+        table[1] = one;
+    }
+    for m in 2..len {
+        let p = sieve.smallest_prime_factor(m as u32).unwrap() as usize;
+        let quotient = m / p;
+        if quotient.is_multiple_of(p) {
+            rest[m] = rest[quotient];
+            exponent[m] = exponent[quotient] + 1;
+        } else {
+            rest[m] = quotient;
+            exponent[m] = 1;
+        }
+        table[m] = combine(table[rest[m]], prime_power(p as u64, exponent[m]));
+    }
+    table
+}
+
+/// Euler's totient, `phi(0) = 0`.
+pub fn phi(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    evaluate(n, 1, phi_prime_power, |a, b| a * b)
+}
+
+/// Moebius function, `mobius(0) = 0`.
+pub fn mobius(n: u64) -> i8 {
+    if n == 0 {
+        return 0;
+    }
+    evaluate(n, 1, mobius_prime_power, |a, b| a * b)
+}
+
+/// Carmichael's function: the exponent of the multiplicative group modulo `n`.
+/// `carmichael_lambda(0) = 0`.
+pub fn carmichael_lambda(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    evaluate(n, 1, carmichael_prime_power, lcm)
+}
+
+/// Sum of the `k`-th powers of the divisors of `n`, `None` if it overflows `u128`.
+/// `sigma_k(0, k) = 0`.
+pub fn sigma_k(n: u64, k: u32) -> Option<u128> {
+    if n == 0 {
+        return Some(0);
+    }
+    evaluate(
+        n,
+        Some(1),
+        |p, e| sigma_k_prime_power(p, e, k),
+        checked_product,
+    )
+}
+
+/// Number of distinct prime factors, `omega(0) = 0`.
+pub fn omega(n: u64) -> u32 {
+    factorize(n).len() as u32
+}
+
+pub fn phi_table(n: u32) -> Vec<u64> {
+    tabulate(n, 0, 1, phi_prime_power, |a, b| a * b)
+}
+
+pub fn mobius_table(n: u32) -> Vec<i8> {
+    tabulate(n, 0, 1, mobius_prime_power, |a, b| a * b)
+}
+
+pub fn carmichael_table(n: u32) -> Vec<u64> {
+    tabulate(n, 0, 1, carmichael_prime_power, lcm)
+}
+
+pub fn sigma_k_table(n: u32, k: u32) -> Option<Vec<u128>> {
+    tabulate(
+        n,
+        Some(0),
+        Some(1),
+        |p, e| sigma_k_prime_power(p, e, k),
+        checked_product,
+    )
+    .into_iter()
+    .collect()
+}
+
+pub fn omega_table(n: u32) -> Vec<u32> {
+    tabulate(n, 0, 0, |_, _| 1, |a, b| a + b)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::factorization::{
+        carmichael_lambda, carmichael_table, divisors, factorize, mobius, mobius_table,
+        num_divisors, omega, omega_table, phi, phi_table, sigma_k, sigma_k_table, sum_divisors,
+    };
+    use crate::nums::gcd;
+    use crate::primes::LinearSieve;
+    use rand::RngCore;
+
+    fn multiply(factors: &[(u64, u32)]) -> u64 {
+        factors.iter().map(|&(p, e)| p.pow(e)).product()
+    }
+
+    #[test]
+    fn factorize_example() {
+        assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize(1), vec![]);
+        assert_eq!(factorize(0), vec![]);
+        assert_eq!(factorize(2), vec![(2, 1)]);
+        assert_eq!(factorize(1 << 63), vec![(2, 63)]);
+        assert_eq!(
+            factorize(u64::MAX),
+            vec![
+                (3, 1),
+                (5, 1),
+                (17, 1),
+                (257, 1),
+                (641, 1),
+                (65537, 1),
+                (6700417, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn factorize_matches_sieve() {
+        let sieve = LinearSieve::new(20_000);
+        for n in 1..=20_000u32 {
+            let expected: Vec<(u64, u32)> = sieve
+                .factorize(n)
+                .into_iter()
+                .map(|(p, e)| (p as u64, e))
+                .collect();
+            assert_eq!(factorize(n as u64), expected);
+        }
+    }
+
+    #[test]
+    fn factorize_semiprimes_near_2_64() {
+        let (p, q) = (4_294_967_291u64, 4_294_967_279u64);
+        assert_eq!(factorize(p * q), vec![(q, 1), (p, 1)]);
+        let (p, q) = (1_000_000_007u64, 1_000_000_009u64);
+        assert_eq!(factorize(p * q), vec![(p, 1), (q, 1)]);
+        let p = 4_294_967_291u64;
+        assert_eq!(factorize(p * p), vec![(p, 2)]);
+        assert_eq!(
+            factorize(18_446_744_073_709_551_557),
+            vec![(18_446_744_073_709_551_557, 1)]
+        );
+    }
+
+    #[test]
+    fn factorize_random() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let n = rng.next_u64();
+            let factors = factorize(n);
+            assert_eq!(multiply(&factors), n);
+            assert!(factors.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+
+    #[test]
+    fn divisors_example() {
+        assert_eq!(divisors(12), vec![1, 2, 3, 4, 6, 12]);
+        assert_eq!(divisors(1), vec![1]);
+        assert_eq!(divisors(0), vec![]);
+        assert_eq!(num_divisors(360), 24);
+        assert_eq!(num_divisors(0), 0);
+        assert_eq!(sum_divisors(12), 28);
+        assert_eq!(sum_divisors(1), 1);
+        for n in 1..500u64 {
+            let naive: Vec<u64> = (1..=n).filter(|d| n % d == 0).collect();
+            assert_eq!(divisors(n), naive);
+            assert_eq!(num_divisors(n), naive.len() as u64);
+            assert_eq!(sum_divisors(n), naive.iter().map(|&d| d as u128).sum());
+        }
+    }
+
+    #[test]
+    fn sum_divisors_beyond_u64() {
+        let n = 3 << 62;
+        assert_eq!(sum_divisors(n), ((1u128 << 63) - 1) * 4);
+    }
+
+    fn naive_carmichael(n: u64) -> u64 {
+        let units: Vec<u64> = (1..=n).filter(|&a| gcd(a, n) == 1).collect();
+        (1..=n)
+            .find(|&k| {
+                units
+                    .iter()
+                    .all(|&a| (0..k).fold(1 % n, |acc, _| acc * a % n) == 1 % n)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn arithmetic_functions_example() {
+        assert_eq!(phi(36), 12);
+        assert_eq!(phi(1), 1);
+        assert_eq!(phi(0), 0);
+        assert_eq!(mobius(30), -1);
+        assert_eq!(mobius(6), 1);
+        assert_eq!(mobius(12), 0);
+        assert_eq!(mobius(1), 1);
+        assert_eq!(carmichael_lambda(8), 2);
+        assert_eq!(carmichael_lambda(561), 80);
+        assert_eq!(carmichael_lambda(1), 1);
+        assert_eq!(sigma_k(12, 0), Some(6));
+        assert_eq!(sigma_k(12, 1), Some(28));
+        assert_eq!(sigma_k(12, 2), Some(210));
+        assert_eq!(sigma_k(u64::MAX, 3), None);
+        assert_eq!(omega(360), 3);
+        assert_eq!(omega(1), 0);
+    }
+
+    #[test]
+    fn arithmetic_functions_brute_force() {
+        for n in 1..200u64 {
+            let divs = divisors(n);
+            assert_eq!(phi(n), (1..=n).filter(|&a| gcd(a, n) == 1).count() as u64);
+            assert_eq!(carmichael_lambda(n), naive_carmichael(n), "{}", n);
+            let squarefree = !(2..=n).any(|d| n % (d * d) == 0);
+            let primes = divs
+                .iter()
+                .filter(|&&d| d > 1 && divisors(d).len() == 2)
+                .count();
+            assert_eq!(omega(n), primes as u32);
+            let expected_mobius = match (squarefree, primes % 2) {
+                (false, _) => 0,
+                (true, 0) => 1,
+                (true, _) => -1,
+            };
+            assert_eq!(mobius(n), expected_mobius);
+            for k in 0..4 {
+                let expected: u128 = divs.iter().map(|&d| (d as u128).pow(k)).sum();
+                assert_eq!(sigma_k(n, k), Some(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn arithmetic_tables_match_single_values() {
+        let n = 5000;
+        let (phis, mobiuses, carmichaels, omegas) = (
+            phi_table(n),
+            mobius_table(n),
+            carmichael_table(n),
+            omega_table(n),
+        );
+        let sigmas = sigma_k_table(n, 2).unwrap();
+        assert_eq!(phis.len(), n as usize + 1);
+        for i in 0..=n as u64 {
+            let idx = i as usize;
+            assert_eq!(phis[idx], phi(i));
+            assert_eq!(mobiuses[idx], mobius(i));
+            assert_eq!(carmichaels[idx], carmichael_lambda(i));
+            assert_eq!(omegas[idx], omega(i));
+            assert_eq!(Some(sigmas[idx]), sigma_k(i, 2));
+        }
+        assert_eq!(sigma_k_table(10, 40), None);
+    }
+}
@@ -1,98 +1,354 @@
+use crate::biguint::BigUint;
+use crate::factorization::factorize;
+use crate::matrix::Matrix;
+use crate::modint::DynModInt;
+use crate::nums::checked_lcm;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
 static FIBO_ENDS: [u8; 60] = [
     0, 1, 1, 2, 3, 5, 8, 3, 1, 4, 5, 9, 4, 3, 7, 0, 7, 7, 4, 1, 5, 6, 1, 7, 8, 5, 3, 8, 1, 9, 0, 9,
     9, 8, 7, 5, 2, 7, 9, 6, 5, 1, 6, 7, 3, 0, 3, 3, 6, 9, 5, 4, 9, 3, 2, 5, 7, 2, 9, 1,
 ];
 
+/// Largest `n` with `F(n)` fitting in a `u128`.
+const MAX_FIBONACCI_U128: u32 = 186;
+
+/// `(F(k), F(k + 1))` by fast doubling, for `k` small enough that nothing overflows.
+fn fibonacci_pair_u128(k: u32) -> (u128, u128) {
+    let (mut a, mut b) = (0u128, 1u128);
+    for bit in (0..32 - k.leading_zeros()).rev() {
+        let even = a * (2 * b - a);
+        let odd = a * a + b * b;
+        if (k >> bit) & 1 == 1 {
+            a = odd;
+            b = even + odd;
+        } else {
+            a = even;
+            b = odd;
+        }
+    }
+    (a, b)
+}
+
+fn checked_fibonacci_u128(n: u32) -> Option<u128> {
+    if n > MAX_FIBONACCI_U128 {
+        return None;
+    }
+    // the last doubling step alone could overflow, so only it is checked
+    let (a, b) = fibonacci_pair_u128(n >> 1);
+    if n & 1 == 1 {
+        a.checked_mul(a)?.checked_add(b.checked_mul(b)?)
+    } else {
+        a.checked_mul(2 * b - a)
+    }
+}
+
+/// `F(n)`, panicking if it does not fit in a `u64` (`n > 93`).
 pub fn fibonacci(n: u32) -> u64 {
-    if n <= 1 {
-        return n as u64;
+    checked_fibonacci(n).expect("fibonacci overflows u64")
+}
+
+pub fn checked_fibonacci(n: u32) -> Option<u64> {
+    checked_fibonacci_u128(n).and_then(|value| u64::try_from(value).ok())
+}
+
+/// `F(n)`, panicking if it does not fit in a `u128` (`n > 186`).
+pub fn fibonacci_u128(n: u32) -> u128 {
+    checked_fibonacci_u128(n).expect("fibonacci overflows u128")
+}
+
+/// `(F(n), F(n + 1)) mod m` by fast doubling.
+fn fibonacci_pair_mod(n: u64, m: u64) -> (DynModInt, DynModInt) {
+    let (mut a, mut b) = (DynModInt::new(0, m), DynModInt::new(1, m));
+    for bit in (0..64 - n.leading_zeros()).rev() {
+        let even = a * (b + b - a);
+        let odd = a * a + b * b;
+        if (n >> bit) & 1 == 1 {
+            a = odd;
+            b = even + odd;
+        } else {
+            a = even;
+            b = odd;
+        }
     }
-    let (mut a, mut b) = (0, 1);
-    for _ in 1..n {
-        let next = a + b;
-        a = b;
-        b = next;
+    (a, b)
+}
+
+/// `F(n) mod m` by fast doubling, for any `n` and without the Pisano period.
+/// Panics if `m` is 0.
+pub fn fibonacci_mod(n: u64, m: u64) -> u64 {
+    fibonacci_pair_mod(n, m).0.value()
+}
+
+/// `F(0) + ... + F(n) mod m`, which is `F(n + 2) - 1`. Panics if `m` is 0.
+pub fn fibonacci_sum_mod(n: u64, m: u64) -> u64 {
+    let (a, b) = fibonacci_pair_mod(n, m);
+    (a + b - DynModInt::new(1, m)).value()
+}
+
+/// `F(from) + ... + F(to) mod m`, which is `F(to + 2) - F(from + 1)`, and 0 for an empty
+/// range. Panics if `m` is 0.
+pub fn fibonacci_range_sum_mod(from: u64, to: u64, m: u64) -> u64 {
+    if from > to {
+        return DynModInt::new(0, m).value();
+    }
+    let (a, b) = fibonacci_pair_mod(to, m);
+    (a + b - fibonacci_pair_mod(from, m).1).value()
+}
+
+/// `F(0)^2 + ... + F(n)^2 mod m`, which is `F(n) * F(n + 1)`. Panics if `m` is 0.
+pub fn fibonacci_square_sum_mod(n: u64, m: u64) -> u64 {
+    let (a, b) = fibonacci_pair_mod(n, m);
+    (a * b).value()
+}
+
+/// Exact `F(n)` for any `n`, using the fast doubling identities
+/// `F(2k) = F(k) * (2 * F(k + 1) - F(k))` and `F(2k + 1) = F(k)^2 + F(k + 1)^2`.
+pub fn fibonacci_big(n: u64) -> BigUint {
+    // (F(k), F(k + 1)) for k being the bits of n read so far
+    let (mut a, mut b) = (BigUint::zero(), BigUint::one());
+    for bit in (0..64 - n.leading_zeros()).rev() {
+        let even = &a * &(&(&b + &b) - &a);
+        let odd = &(&a * &a) + &(&b * &b);
+        if (n >> bit) & 1 == 1 {
+            b = &even + &odd;
+            a = odd;
+        } else {
+            a = even;
+            b = odd;
+        }
     }
-    b
+    a
 }
 
 pub fn fibonacci_ends(n: u64) -> u8 {
     FIBO_ENDS[n as usize % FIBO_ENDS.len()]
 }
 
-pub fn pisano(m: u64) -> u64 {
-    let (mut previous, mut current): (u64, u64) = (0, 1);
-    let mut counter = 0;
-    loop {
-        counter += 1;
-        let tmp = previous;
-        previous = current;
-        current = (tmp + current) % m;
-        if matches!((previous, current), (0, 1)) {
-            return counter;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PisanoError {
+    ZeroModulus,
+    /// The period does not fit in a `u64`.
+    Overflow,
+}
+
+impl Display for PisanoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PisanoError::ZeroModulus => write!(f, "pisano period of modulus 0"),
+            PisanoError::Overflow => write!(f, "pisano period does not fit in u64"),
         }
     }
 }
 
-pub fn fibonacci_modulus(n: u64, m: u64) -> u64 {
-    let pisano = pisano(m);
-    let new_n = n % pisano;
-    if new_n <= 1 {
-        return new_n % m;
+impl Error for PisanoError {}
+
+/// Whether `[[1, 1], [1, 0]]^n` is the identity mod `m`, for `n < 2^65`.
+fn is_fibonacci_period(n: u128, m: u64) -> bool {
+    let (one, zero) = (DynModInt::new(1, m), DynModInt::new(0, m));
+    let q = Matrix::from_rows(vec![vec![one, one], vec![one, zero]]);
+    let half = q.pow((n >> 1) as u64);
+    let mut power = &half * &half;
+    if n & 1 == 1 {
+        power = &power * &q;
     }
-    let (mut a, mut b) = (0, 1);
-    for _ in 1..new_n {
-        let next = (a + b) % m;
-        a = b;
-        b = next;
+    power == Matrix::identity_like(2, one)
+}
+
+/// Period of the Fibonacci numbers modulo the prime `p`, which divides `p - 1` when
+/// `p = ±1 mod 5` and `2 (p + 1)` otherwise.
+fn prime_pisano(p: u64) -> u128 {
+    match p {
+        2 => return 3,
+        5 => return 20,
+        _ => {}
     }
-    b % m
+    let (multiple, mut factors) = if matches!(p % 5, 1 | 4) {
+        ((p - 1) as u128, factorize(p - 1))
+    } else {
+        let mut factors = factorize(p + 1);
+        factors[0].1 += 1;
+        (2 * (p as u128 + 1), factors)
+    };
+    // the period is the order of the matrix, found by removing factors while possible
+    let mut period = multiple;
+    for (q, e) in factors.drain(..) {
+        for _ in 0..e {
+            if is_fibonacci_period(period / q as u128, p) {
+                period /= q as u128;
+            } else {
+                break;
+            }
+        }
+    }
+    period
+}
+
+/// Period of the Fibonacci numbers modulo `m`, combining the periods `p^(e - 1) * pisano(p)`
+/// of the prime powers in the factorization of `m`. That formula relies on Wall's conjecture,
+/// `pisano(p^2) != pisano(p)`, which only matters for primes with `p^2` dividing `m`. For a `u64`
+/// modulus those are below `2^32`, far under the bound the conjecture has been verified to, so
+/// the result is exact for every `m`.
+pub fn pisano(m: u64) -> Result<u64, PisanoError> {
+    if m == 0 {
+        return Err(PisanoError::ZeroModulus);
+    }
+    factorize(m).into_iter().try_fold(1u64, |period, (p, e)| {
+        let prime_power_period =
+            (1..e).try_fold(prime_pisano(p), |acc, _| acc.checked_mul(p as u128));
+        prime_power_period
+            .and_then(|value| u64::try_from(value).ok())
+            .and_then(|value| checked_lcm(period, value))
+            .ok_or(PisanoError::Overflow)
+    })
+}
+
+/// `F(n) mod m` in `O(log n)`, the same as `fibonacci_mod`. Panics if `m` is 0.
+pub fn fibonacci_modulus(n: u64, m: u64) -> u64 {
+    fibonacci_mod(n, m)
 }
 
 pub fn fibonacci_sum_ends(n: u64) -> u8 {
-    let new_n = (n + 1) as usize % FIBO_ENDS.len();
-    FIBO_ENDS
-        .iter()
-        .take(new_n)
-        .fold(0u8, |accum, value| (accum + value) % 10)
+    fibonacci_sum_mod(n, 10) as u8
 }
 
 pub fn fibonacci_sum_range_ends(m: u64, n: u64) -> u8 {
-    if m == n {
-        return fibonacci_ends(n);
-    }
-    let new_m = m as usize % FIBO_ENDS.len();
-    let new_n = n as usize % FIBO_ENDS.len();
-    if new_m < new_n {
-        (new_m..new_n + 1)
-            .map(|i| FIBO_ENDS[i])
-            .fold(0u8, |acc, value| (acc + value) % 10)
-    } else {
-        let prev = (0..new_n + 1)
-            .map(|i| FIBO_ENDS[i])
-            .fold(0u8, |acc, value| (acc + value) % 10);
-        let next = (new_m..FIBO_ENDS.len())
-            .map(|i| FIBO_ENDS[i])
-            .fold(0u8, |acc, value| (acc + value) % 10);
-        (prev + next) % 10
-    }
+    fibonacci_range_sum_mod(m, n, 10) as u8
 }
 
 pub fn fibonacci_sum_square_ends(n: u64) -> u8 {
-    let new_n = (n + 1) as usize % FIBO_ENDS.len();
-    FIBO_ENDS
+    fibonacci_square_sum_mod(n, 10) as u8
+}
+
+/// Indices `k >= 2`, in decreasing order and never consecutive, of the Fibonacci numbers
+/// `F(k)` adding up to `n`. Empty for 0.
+pub fn zeckendorf(mut n: u64) -> Vec<u32> {
+    let mut indices = Vec::new();
+    let mut k = 93;
+    while n > 0 {
+        while fibonacci(k) > n {
+            k -= 1;
+        }
+        indices.push(k);
+        n -= fibonacci(k);
+        k -= 1;
+    }
+    indices
+}
+
+/// Inverse of `zeckendorf`, `None` if `indices` is not a Zeckendorf representation or its
+/// sum does not fit in a `u64`.
+pub fn from_zeckendorf(indices: &[u32]) -> Option<u64> {
+    if indices.iter().any(|&k| k < 2) || indices.windows(2).any(|w| w[0] <= w[1] + 1) {
+        return None;
+    }
+    indices
         .iter()
-        .take(new_n)
-        .map(|v| v * v)
-        .fold(0u8, |accum, value| (accum + value) % 10)
+        .try_fold(0u64, |sum, &k| sum.checked_add(checked_fibonacci(k)?))
+}
+
+/// Largest Fibonacci index appearing in the code of a `u64`, as `u64::MAX + 1 < F(94)`.
+const MAX_CODE_INDEX: u32 = 93;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FibonacciDecodeError {
+    /// The stream ends in the middle of a codeword.
+    Truncated,
+    /// A codeword is larger than `u64::MAX + 1`.
+    Overflow,
+}
+
+impl Display for FibonacciDecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FibonacciDecodeError::Truncated => write!(f, "truncated fibonacci codeword"),
+            FibonacciDecodeError::Overflow => write!(f, "fibonacci codeword does not fit in u64"),
+        }
+    }
+}
+
+impl Error for FibonacciDecodeError {}
+
+/// Fibonacci coding of `values`: every `n` becomes the Zeckendorf bits of `n + 1` from the
+/// smallest index up, followed by an extra 1 so each codeword ends in "11". Bits are packed
+/// most significant first and the last byte is padded with zeros.
+pub fn fibonacci_encode(values: &[u64]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut length = 0usize;
+    let mut push = |bit: bool| {
+        if length.is_multiple_of(8) {
+            bytes.push(0);
+        }
+        if bit {
+            *bytes.last_mut().unwrap() |= 0x80 >> (length % 8);
+        }
+        length += 1;
+    };
+    for &value in values {
+        let mut rest = value as u128 + 1;
+        let mut bits = [false; MAX_CODE_INDEX as usize + 1];
+        let mut top = MAX_CODE_INDEX;
+        while fibonacci_u128(top) > rest {
+            top -= 1;
+        }
+        for k in (2..=top).rev() {
+            if fibonacci_u128(k) <= rest {
+                rest -= fibonacci_u128(k);
+                bits[k as usize] = true;
+            }
+        }
+        bits[2..=top as usize].iter().for_each(|&bit| push(bit));
+        push(true);
+    }
+    bytes
+}
+
+/// Inverse of `fibonacci_encode`. Up to 7 trailing zero bits are taken as padding.
+pub fn fibonacci_decode(bytes: &[u8]) -> Result<Vec<u64>, FibonacciDecodeError> {
+    let mut values = Vec::new();
+    let (mut sum, mut k, mut previous, mut pending) = (0u128, 2u32, false, 0usize);
+    for i in 0..bytes.len() * 8 {
+        let bit = bytes[i / 8] & (0x80 >> (i % 8)) != 0;
+        pending += 1;
+        if bit && previous {
+            let value = u64::try_from(sum - 1).map_err(|_| FibonacciDecodeError::Overflow)?;
+            values.push(value);
+            sum = 0;
+            k = 2;
+            previous = false;
+            pending = 0;
+            continue;
+        }
+        if bit {
+            if k > MAX_CODE_INDEX {
+                return Err(FibonacciDecodeError::Overflow);
+            }
+            sum += fibonacci_u128(k);
+        }
+        previous = bit;
+        k += 1;
+    }
+    if sum > 0 || pending >= 8 {
+        return Err(FibonacciDecodeError::Truncated);
+    }
+    Ok(values)
 }
 
 #[cfg(test)]
 mod test {
+    use crate::biguint::BigUint;
     use crate::fibonacci::{
-        fibonacci, fibonacci_ends, fibonacci_modulus, fibonacci_sum_ends, fibonacci_sum_range_ends,
-        fibonacci_sum_square_ends, pisano,
+        checked_fibonacci, fibonacci, fibonacci_big, fibonacci_decode, fibonacci_encode,
+        fibonacci_ends, fibonacci_mod, fibonacci_modulus, fibonacci_range_sum_mod,
+        fibonacci_square_sum_mod, fibonacci_sum_ends, fibonacci_sum_mod, fibonacci_sum_range_ends,
+        fibonacci_sum_square_ends, fibonacci_u128, from_zeckendorf, pisano, zeckendorf,
+        FibonacciDecodeError, PisanoError, FIBO_ENDS,
     };
+    use crate::matrix::Matrix;
+    use crate::modint::DynModInt;
+    use rand::RngCore;
 
     #[test]
     fn test_seq() {
@@ -107,12 +363,106 @@ mod test {
         }
     }
 
+    #[test]
+    fn fibo_ends_table_matches_fibonacci_mod() {
+        for (i, &end) in FIBO_ENDS.iter().enumerate() {
+            assert_eq!(end as u64, fibonacci_mod(i as u64, 10));
+        }
+    }
+
+    #[test]
+    fn fibo_checked_limits() {
+        assert_eq!(checked_fibonacci(93), Some(12_200_160_415_121_876_738));
+        assert_eq!(checked_fibonacci(94), None);
+        assert_eq!(
+            fibonacci_u128(186),
+            332_825_110_087_067_562_321_196_029_789_634_457_848
+        );
+        assert_eq!(checked_fibonacci(u32::MAX), None);
+        let mut previous = (0u128, 1u128);
+        for n in 0..=186 {
+            assert_eq!(fibonacci_u128(n), previous.0);
+            previous = (previous.1, previous.0.wrapping_add(previous.1));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn fibo_u128_overflow() {
+        fibonacci_u128(187);
+    }
+
+    #[test]
+    fn fibo_mod_matches_matrix() {
+        for &m in &[1u64, 2, 10, 235, 1_000_000_007, u64::MAX] {
+            // F(n) is the top right entry of [[1, 1], [1, 0]]^n
+            let (one, zero) = (DynModInt::new(1, m), DynModInt::new(0, m));
+            let q = Matrix::from_rows(vec![vec![one, one], vec![one, zero]]);
+            for &n in &[
+                0u64,
+                1,
+                2,
+                93,
+                1000,
+                1548276540,
+                1_000_000_000_000_000_000,
+                u64::MAX,
+            ] {
+                assert_eq!(fibonacci_mod(n, m), q.pow(n)[(0, 1)].value(), "{} {}", n, m);
+            }
+        }
+    }
+
     #[test]
     fn pisano_example() {
-        let expected = 20u64;
+        let expected = Ok(20u64);
         assert_eq!(pisano(5), expected);
     }
 
+    #[test]
+    fn pisano_small_moduli() {
+        assert_eq!(pisano(0), Err(PisanoError::ZeroModulus));
+        assert_eq!(pisano(1), Ok(1));
+        assert_eq!(pisano(2), Ok(3));
+        assert_eq!(pisano(10), Ok(60));
+    }
+
+    #[test]
+    fn pisano_matches_naive() {
+        for m in 2..3000u64 {
+            let (mut previous, mut current, mut period) = (0, 1, 0);
+            loop {
+                period += 1;
+                let next = (previous + current) % m;
+                previous = current;
+                current = next;
+                if (previous, current) == (0, 1) {
+                    break;
+                }
+            }
+            assert_eq!(pisano(m), Ok(period), "{}", m);
+        }
+    }
+
+    #[test]
+    fn pisano_large_moduli() {
+        assert_eq!(pisano(1_000_000_000_000), Ok(1_500_000_000_000));
+        assert_eq!(pisano(4_294_967_197), Ok(8_589_934_396));
+        assert_eq!(
+            pisano(4_294_967_197 * 4_294_967_197),
+            Err(PisanoError::Overflow)
+        );
+        for &m in &[
+            1_000_000_007u64,
+            999_999_999_989,
+            4_294_967_291 * 4_294_967_279,
+        ] {
+            let period = pisano(m).unwrap();
+            assert_eq!(fibonacci_modulus(period, m), 0);
+            assert_eq!(fibonacci_modulus(period + 1, m), 1);
+        }
+    }
+
     #[test]
     fn fibo_mod() {
         let (n, m) = (1548276540, 235);
@@ -134,6 +484,20 @@ mod test {
         assert_eq!(fibonacci_modulus(n, m), expected);
     }
 
+    #[test]
+    fn fibo_mod_huge_n_large_modulus() {
+        assert_eq!(
+            fibonacci_modulus(1_000_000_000_000_000_000, 1_000_000_007),
+            209783453
+        );
+        assert_eq!(
+            fibonacci_modulus(1_000_000_000_000_000_000, (1 << 61) - 1),
+            1024960830501646393
+        );
+        assert_eq!(fibonacci_modulus(u64::MAX, 1), 0);
+        assert_eq!(fibonacci_modulus(0, 7), 0);
+    }
+
     #[test]
     fn fibo_sum_ends_example() {
         assert_eq!(fibonacci_sum_ends(3), 4);
@@ -160,10 +524,150 @@ mod test {
         assert_eq!(fibonacci_sum_range_ends(5618252, 6583591534156), 6);
     }
 
+    #[test]
+    fn fibo_sums_mod_match_naive() {
+        for &m in &[1u64, 2, 10, 97, 1_000_000_007] {
+            let (mut sum, mut square_sum) = (0u64, 0u64);
+            let prefix: Vec<u64> = (0..=150u64)
+                .map(|n| {
+                    let f = fibonacci_mod(n, m);
+                    sum = (sum + f) % m;
+                    square_sum = ((square_sum as u128 + f as u128 * f as u128) % m as u128) as u64;
+                    assert_eq!(fibonacci_sum_mod(n, m), sum);
+                    assert_eq!(fibonacci_square_sum_mod(n, m), square_sum);
+                    sum
+                })
+                .collect();
+            for from in 0..40usize {
+                for to in from..60 {
+                    let before = if from == 0 { 0 } else { prefix[from - 1] };
+                    let expected = (prefix[to] + m - before) % m;
+                    assert_eq!(fibonacci_range_sum_mod(from as u64, to as u64, m), expected);
+                }
+            }
+            assert_eq!(fibonacci_range_sum_mod(5, 4, m), 0);
+        }
+    }
+
+    #[test]
+    fn fibo_sums_mod_huge_n() {
+        let m = 1_000_000_007;
+        let n = u64::MAX;
+        // F(n + 2) - 1 written as F(n) + F(n + 1) - 1
+        let expected = (fibonacci_mod(n, m) + fibonacci_mod(n - 1, m) + fibonacci_mod(n, m)) % m;
+        assert_eq!(fibonacci_sum_mod(n, m), (expected + m - 1) % m);
+        assert_eq!(fibonacci_range_sum_mod(0, n, m), fibonacci_sum_mod(n, m));
+    }
+
     #[test]
     fn fibo_sum_square_ends() {
         assert_eq!(fibonacci_sum_square_ends(7), 3);
         assert_eq!(fibonacci_sum_square_ends(73), 1);
         assert_eq!(fibonacci_sum_square_ends(1234567890), 0);
     }
+
+    #[test]
+    fn fibo_big_matches_u64() {
+        for n in 0..=93u32 {
+            assert_eq!(fibonacci_big(n as u64), BigUint::from(fibonacci(n)));
+        }
+    }
+
+    #[test]
+    fn fibo_big_1000() {
+        let expected = "43466557686937456435688527675040625802564660517371780402481729089536555417949051890403879840079255169295922593080322634775209689623239873322471161642996440906533187938298969649928516003704476137795166849228875";
+        assert_eq!(fibonacci_big(1000).to_string(), expected);
+    }
+
+    #[test]
+    fn fibo_big_100000() {
+        let f = fibonacci_big(100_000).to_string();
+        assert_eq!(f.len(), 20899);
+        assert_eq!(&f[..20], "25974069347221724166");
+        assert_eq!(&f[f.len() - 20..], "49895374653428746875");
+    }
+
+    #[test]
+    fn zeckendorf_example() {
+        assert_eq!(zeckendorf(100), vec![11, 6, 4]);
+        assert_eq!(zeckendorf(0), vec![]);
+        assert_eq!(zeckendorf(1), vec![2]);
+        assert_eq!(from_zeckendorf(&[11, 6, 4]), Some(100));
+        assert_eq!(from_zeckendorf(&[]), Some(0));
+        assert_eq!(from_zeckendorf(&[5, 4]), None);
+        assert_eq!(from_zeckendorf(&[4, 6]), None);
+        assert_eq!(from_zeckendorf(&[1]), None);
+        assert_eq!(from_zeckendorf(&[94]), None);
+    }
+
+    #[test]
+    fn zeckendorf_round_trip() {
+        let mut rng = rand::thread_rng();
+        let values = (0..1000u64).chain((0..1000).map(|_| rng.next_u64()));
+        for n in values.chain(std::iter::once(u64::MAX)) {
+            let indices = zeckendorf(n);
+            assert!(indices.windows(2).all(|w| w[0] > w[1] + 1));
+            assert_eq!(from_zeckendorf(&indices), Some(n));
+        }
+    }
+
+    #[test]
+    fn fibonacci_code_example() {
+        // 11 011 0011 1011 for 1, 2, 3 and 4
+        assert_eq!(
+            fibonacci_encode(&[0, 1, 2, 3]),
+            vec![0b1101_1001, 0b1101_1000]
+        );
+        assert_eq!(fibonacci_encode(&[0]), vec![0b1100_0000]);
+        assert_eq!(fibonacci_encode(&[]), vec![]);
+        assert_eq!(
+            fibonacci_decode(&[0b1101_1001, 0b1101_1000]),
+            Ok(vec![0, 1, 2, 3])
+        );
+        assert_eq!(fibonacci_decode(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn fibonacci_code_errors() {
+        assert_eq!(
+            fibonacci_decode(&[0b1000_0000]),
+            Err(FibonacciDecodeError::Truncated)
+        );
+        assert_eq!(fibonacci_decode(&[0]), Err(FibonacciDecodeError::Truncated));
+        assert_eq!(
+            fibonacci_decode(&[0b1100_0000, 0]),
+            Err(FibonacciDecodeError::Truncated)
+        );
+        // 92 zeros for the indices 2 to 93, then F(94) and the terminating 1
+        let mut bytes = vec![0u8; 12];
+        bytes[11] = 0b0000_1100;
+        assert_eq!(
+            fibonacci_decode(&bytes),
+            Err(FibonacciDecodeError::Overflow)
+        );
+        // F(89) + F(91) + F(93) exceeds u64::MAX + 1
+        let mut bytes = vec![0u8; 12];
+        bytes[10] = 0b0000_0001;
+        bytes[11] = 0b0101_1000;
+        assert_eq!(
+            fibonacci_decode(&bytes),
+            Err(FibonacciDecodeError::Overflow)
+        );
+    }
+
+    #[test]
+    fn fibonacci_code_round_trip() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(
+            fibonacci_decode(&fibonacci_encode(&[u64::MAX, 0, u64::MAX])),
+            Ok(vec![u64::MAX, 0, u64::MAX])
+        );
+        for _ in 0..200 {
+            let length = rng.next_u32() as usize % 50;
+            let values: Vec<u64> = (0..length)
+                .map(|_| rng.next_u64() >> (rng.next_u32() % 64))
+                .collect();
+            assert_eq!(fibonacci_decode(&fibonacci_encode(&values)), Ok(values));
+        }
+    }
 }
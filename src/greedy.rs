@@ -1,3 +1,4 @@
+use crate::ratio::Ratio;
 use std::ops::Div;
 
 pub fn coin_exchange(n: u64) -> u64 {
@@ -39,6 +40,28 @@ pub fn knapsack(max_weigh: u64, items: &mut [(u64, u64)]) -> f64 {
     total_value
 }
 
+/// Same as `knapsack` with `(value, weigh)` items, but with an exact rational result.
+pub fn knapsack_exact(max_weigh: u64, items: &mut [(u64, u64)]) -> Ratio<u64> {
+    items.sort_by(|&(value_a, weigh_a), &(value_b, weigh_b)| {
+        Ratio::new(value_b, weigh_b).cmp(&Ratio::new(value_a, weigh_a))
+    });
+    let mut left_weigh = max_weigh;
+    let mut total_value = Ratio::from_integer(0);
+    for &(value, weigh) in items.iter() {
+        if left_weigh == 0 {
+            break;
+        }
+        if weigh <= left_weigh {
+            total_value = total_value + Ratio::from_integer(value);
+            left_weigh -= weigh;
+        } else {
+            total_value = total_value + Ratio::new(value, weigh) * Ratio::from_integer(left_weigh);
+            left_weigh = 0;
+        }
+    }
+    total_value
+}
+
 pub fn gas_stations(distance: u64, gas_distance: u64, gas_stations: &mut Vec<u64>) -> Option<u64> {
     if gas_stations.is_empty() {
         return if gas_distance >= distance {
@@ -138,6 +161,15 @@ mod test {
         assert_eq!(&res.to_string()[..6], "166.66");
     }
 
+    #[test]
+    fn test_knapsack_exact_example() {
+        let mut items = [(60, 20), (100, 50), (120, 30)];
+        assert_eq!(knapsack_exact(50, &mut items), Ratio::from_integer(180));
+
+        let res = knapsack_exact(10, &mut [(500, 30)]);
+        assert_eq!(res, Ratio::new(500, 3));
+    }
+
     #[test]
     fn gas_stations_example() {
         assert_eq!(
@@ -1,7 +1,17 @@
+pub mod biguint;
+pub mod continued_fraction;
 pub mod dynamic;
+pub mod factorization;
 pub mod fibonacci;
 pub mod greedy;
+pub mod matrix;
+pub mod modint;
 pub mod nums;
 pub mod pairwise_product;
+pub mod primes;
+pub mod ratio;
+pub mod recurrence;
 pub mod search;
 pub mod sort;
+pub mod subarray;
+pub mod top_k;
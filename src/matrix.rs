@@ -0,0 +1,219 @@
+use crate::modint::{DynModInt, ModInt};
+use std::ops::{Add, Index, IndexMut, Mul};
+
+/// Values with an addition and a multiplication, plus their identities.
+/// The identities are taken from an existing value so that types carrying runtime state,
+/// like the modulus of a `DynModInt`, can build them.
+pub trait Semiring: Copy + Add<Output = Self> + Mul<Output = Self> {
+    fn zero_like(&self) -> Self;
+    fn one_like(&self) -> Self;
+}
+
+macro_rules! impl_semiring {
+    ($zero:expr, $one:expr; $($t:ty),*) => {
+        $(
+            impl Semiring for $t {
+                fn zero_like(&self) -> Self {
+                    $zero
+                }
+
+                fn one_like(&self) -> Self {
+                    $one
+                }
+            }
+        )*
+    };
+}
+
+impl_semiring!(0, 1; u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_semiring!(0.0, 1.0; f32, f64);
+
+impl<const M: u64> Semiring for ModInt<M> {
+    fn zero_like(&self) -> Self {
+        ModInt::new(0)
+    }
+
+    fn one_like(&self) -> Self {
+        ModInt::new(1)
+    }
+}
+
+impl Semiring for DynModInt {
+    fn zero_like(&self) -> Self {
+        DynModInt::new(0, self.modulus())
+    }
+
+    fn one_like(&self) -> Self {
+        DynModInt::new(1, self.modulus())
+    }
+}
+
+/// Dense row-major matrix over a semiring.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T: Semiring> Matrix<T> {
+    /// Panics if the rows have different lengths or there are none.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        assert!(!rows.is_empty() && !rows[0].is_empty(), "empty matrix");
+        let cols = rows[0].len();
+        assert!(rows.iter().all(|row| row.len() == cols), "ragged rows");
+        Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    /// `n x n` identity, with the identities taken from `sample`.
+    pub fn identity_like(n: usize, sample: T) -> Self {
+        let mut data = vec![sample.zero_like(); n * n];
+        for i in 0..n {
+            data[i * n + i] = sample.one_like();
+        }
+        Matrix {
+            rows: n,
+            cols: n,
+            data,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// `self^exp` by repeated squaring. Panics if the matrix is not square.
+    pub fn pow(&self, mut exp: u64) -> Self {
+        assert_eq!(self.rows, self.cols, "only square matrices have powers");
+        let mut result = Matrix::identity_like(self.rows, self.data[0]);
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(row < self.rows && col < self.cols, "index out of bounds");
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(row < self.rows && col < self.cols, "index out of bounds");
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+impl<T: Semiring> Mul for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    /// Panics if the inner dimensions differ.
+    fn mul(self, other: &Matrix<T>) -> Matrix<T> {
+        assert_eq!(self.cols, other.rows, "mismatched dimensions");
+        let zero = self.data[0].zero_like();
+        let mut data = vec![zero; self.rows * other.cols];
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self[(i, k)];
+                for j in 0..other.cols {
+                    let cell = &mut data[i * other.cols + j];
+                    *cell = *cell + a * other[(k, j)];
+                }
+            }
+        }
+        Matrix {
+            rows: self.rows,
+            cols: other.cols,
+            data,
+        }
+    }
+}
+
+impl<T: Semiring> Mul for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, other: Matrix<T>) -> Matrix<T> {
+        &self * &other
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::matrix::Matrix;
+    use crate::modint::{DynModInt, ModInt};
+
+    #[test]
+    fn mul_example() {
+        let a = Matrix::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        let b = Matrix::from_rows(vec![vec![7, 8], vec![9, 10], vec![11, 12]]);
+        let product = &a * &b;
+        assert_eq!(
+            product,
+            Matrix::from_rows(vec![vec![58, 64], vec![139, 154]])
+        );
+        assert_eq!((product.rows(), product.cols()), (2, 2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn mul_mismatched_dimensions() {
+        let a = Matrix::from_rows(vec![vec![1, 2, 3]]);
+        let _ = &a * &a;
+    }
+
+    #[test]
+    fn pow_matches_repeated_mul() {
+        let m = Matrix::from_rows(vec![vec![2i64, -1, 0], vec![1, 3, 1], vec![0, 1, -2]]);
+        let mut expected = Matrix::identity_like(3, 0i64);
+        for exp in 0..12 {
+            assert_eq!(m.pow(exp), expected);
+            expected = &expected * &m;
+        }
+    }
+
+    #[test]
+    fn pow_float() {
+        let rotation = Matrix::from_rows(vec![vec![0.0, -1.0], vec![1.0, 0.0]]);
+        assert_eq!(rotation.pow(4), Matrix::identity_like(2, 0.0));
+    }
+
+    #[test]
+    fn pow_modint_fibonacci() {
+        type Mod = ModInt<1_000_000_007>;
+        let q = Matrix::from_rows(vec![
+            vec![Mod::new(1), Mod::new(1)],
+            vec![Mod::new(1), Mod::new(0)],
+        ]);
+        assert_eq!(
+            q.pow(90)[(0, 1)].value(),
+            2_880_067_194_370_816_120 % 1_000_000_007
+        );
+
+        let one = DynModInt::new(1, 10);
+        let q = Matrix::from_rows(vec![vec![one, one], vec![one, DynModInt::new(0, 10)]]);
+        let mut power = q.pow(1_000_000_000_000);
+        assert_eq!(power[(0, 1)].value(), 5);
+        power[(0, 1)] = DynModInt::new(7, 10);
+        assert_eq!(power[(0, 1)].value(), 7);
+    }
+}
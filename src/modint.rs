@@ -0,0 +1,365 @@
+use crate::nums::{mod_inverse, mul_mod, pow_mod};
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+pub(crate) fn add_mod(a: u64, b: u64, m: u64) -> u64 {
+    let (sum, overflow) = a.overflowing_add(b);
+    if overflow || sum >= m {
+        sum.wrapping_sub(m)
+    } else {
+        sum
+    }
+}
+
+fn sub_mod(a: u64, b: u64, m: u64) -> u64 {
+    if a >= b {
+        a - b
+    } else {
+        m - (b - a)
+    }
+}
+
+/// Integer modulo the compile-time modulus `M`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ModInt<const M: u64>(u64);
+
+impl<const M: u64> ModInt<M> {
+    const NON_ZERO_MODULUS: () = assert!(M > 0, "modulus must be positive");
+
+    pub fn new(value: u64) -> Self {
+        let () = Self::NON_ZERO_MODULUS;
+        ModInt(value % M)
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    pub fn modulus(self) -> u64 {
+        M
+    }
+
+    pub fn pow(self, exp: u64) -> Self {
+        ModInt(pow_mod(self.0, exp, M))
+    }
+
+    pub fn inverse(self) -> Option<Self> {
+        mod_inverse(self.0, M).map(ModInt)
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        ModInt::new(value)
+    }
+}
+
+impl<const M: u64> Display for ModInt<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        ModInt(add_mod(self.0, rhs.0, M))
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        ModInt(sub_mod(self.0, rhs.0, M))
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        ModInt(mul_mod(self.0, rhs.0, M))
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        ModInt(sub_mod(0, self.0, M))
+    }
+}
+
+impl<const M: u64> Div for ModInt<M> {
+    type Output = Self;
+
+    /// Panics if `rhs` has no inverse modulo `M`.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inverse().expect("divisor is not invertible")
+    }
+}
+
+/// Integer modulo a modulus only known at runtime.
+/// Operating on values with different moduli panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DynModInt {
+    value: u64,
+    modulus: u64,
+}
+
+impl DynModInt {
+    pub fn new(value: u64, modulus: u64) -> Self {
+        assert!(modulus > 0, "modulus must be positive");
+        DynModInt {
+            value: value % modulus,
+            modulus,
+        }
+    }
+
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    pub fn modulus(self) -> u64 {
+        self.modulus
+    }
+
+    pub fn pow(self, exp: u64) -> Self {
+        self.with_value(pow_mod(self.value, exp, self.modulus))
+    }
+
+    pub fn inverse(self) -> Option<Self> {
+        mod_inverse(self.value, self.modulus).map(|v| self.with_value(v))
+    }
+
+    fn with_value(self, value: u64) -> Self {
+        DynModInt {
+            value,
+            modulus: self.modulus,
+        }
+    }
+
+    fn common_modulus(self, other: Self) -> u64 {
+        assert_eq!(self.modulus, other.modulus, "mismatched moduli");
+        self.modulus
+    }
+}
+
+impl Display for DynModInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl Add for DynModInt {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let m = self.common_modulus(rhs);
+        self.with_value(add_mod(self.value, rhs.value, m))
+    }
+}
+
+impl Sub for DynModInt {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let m = self.common_modulus(rhs);
+        self.with_value(sub_mod(self.value, rhs.value, m))
+    }
+}
+
+impl Mul for DynModInt {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let m = self.common_modulus(rhs);
+        self.with_value(mul_mod(self.value, rhs.value, m))
+    }
+}
+
+impl Neg for DynModInt {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.with_value(sub_mod(0, self.value, self.modulus))
+    }
+}
+
+impl Div for DynModInt {
+    type Output = Self;
+
+    /// Panics if `rhs` has no inverse modulo the shared modulus.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self.common_modulus(rhs);
+        self * rhs.inverse().expect("divisor is not invertible")
+    }
+}
+
+/// Montgomery multiplication context for an odd modulus.
+/// Values passed to `mul` and `pow_montgomery` must already be in Montgomery form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Montgomery {
+    modulus: u64,
+    /// `modulus^-1 mod 2^64`
+    inverse: u64,
+    /// `2^128 mod modulus`
+    r2: u64,
+}
+
+impl Montgomery {
+    pub fn new(modulus: u64) -> Self {
+        assert!(modulus % 2 == 1, "montgomery modulus must be odd");
+        // every iteration of Newton's method doubles the correct low bits, starting from 3
+        let mut inverse = modulus;
+        for _ in 0..5 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(modulus.wrapping_mul(inverse)));
+        }
+        let r = ((1u128 << 64) % modulus as u128) as u64;
+        Montgomery {
+            modulus,
+            inverse,
+            r2: mul_mod(r, r, modulus),
+        }
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    /// Computes `t * 2^-64 mod modulus`, for `t < modulus * 2^64`.
+    fn reduce(&self, t: u128) -> u64 {
+        let m = (t as u64).wrapping_mul(self.inverse);
+        let mn = ((m as u128 * self.modulus as u128) >> 64) as u64;
+        let high = (t >> 64) as u64;
+        if high >= mn {
+            high - mn
+        } else {
+            high.wrapping_sub(mn).wrapping_add(self.modulus)
+        }
+    }
+
+    pub fn to_montgomery(&self, value: u64) -> u64 {
+        self.reduce((value % self.modulus) as u128 * self.r2 as u128)
+    }
+
+    pub fn from_montgomery(&self, value: u64) -> u64 {
+        self.reduce(value as u128)
+    }
+
+    pub fn one(&self) -> u64 {
+        self.to_montgomery(1)
+    }
+
+    pub fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 * b as u128)
+    }
+
+    pub fn pow_montgomery(&self, mut base: u64, mut exp: u64) -> u64 {
+        let mut res = self.one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = self.mul(res, base);
+            }
+            base = self.mul(base, base);
+            exp >>= 1;
+        }
+        res
+    }
+
+    /// `base^exp mod modulus` for plain (non Montgomery) values.
+    pub fn pow(&self, base: u64, exp: u64) -> u64 {
+        self.from_montgomery(self.pow_montgomery(self.to_montgomery(base), exp))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::modint::{DynModInt, ModInt, Montgomery};
+    use crate::nums::mul_mod;
+    use rand::RngCore;
+
+    type Mod7 = ModInt<7>;
+    type ModP = ModInt<1_000_000_007>;
+
+    #[test]
+    fn modint_arithmetic() {
+        let (a, b) = (Mod7::new(5), Mod7::new(4));
+        assert_eq!((a + b).value(), 2);
+        assert_eq!((a - b).value(), 1);
+        assert_eq!((b - a).value(), 6);
+        assert_eq!((a * b).value(), 6);
+        assert_eq!((-a).value(), 2);
+        assert_eq!((-Mod7::new(0)).value(), 0);
+        assert_eq!((a / b) * b, a);
+        assert_eq!(Mod7::from(15), Mod7::new(1));
+    }
+
+    #[test]
+    fn modint_pow_and_inverse() {
+        let a = ModP::new(123_456_789);
+        assert_eq!(a.pow(1_000_000_006).value(), 1);
+        assert_eq!(a * a.inverse().unwrap(), ModP::new(1));
+        assert_eq!(ModInt::<10>::new(4).inverse(), None);
+        assert_eq!(ModInt::<1>::new(4).pow(0).value(), 0);
+    }
+
+    #[test]
+    fn modint_large_modulus() {
+        type Big = ModInt<{ u64::MAX - 58 }>;
+        let a = Big::new(u64::MAX - 60);
+        let b = Big::new(u64::MAX - 59);
+        assert_eq!((a + b).value(), u64::MAX - 61);
+        assert_eq!((a - b).value(), u64::MAX - 59);
+        assert_eq!((a * b).value(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn modint_division_by_non_invertible() {
+        let _ = ModInt::<10>::new(3) / ModInt::<10>::new(4);
+    }
+
+    #[test]
+    fn dyn_modint_arithmetic() {
+        let (a, b) = (DynModInt::new(5, 7), DynModInt::new(4, 7));
+        assert_eq!((a + b).value(), 2);
+        assert_eq!((a - b).value(), 1);
+        assert_eq!((a * b).value(), 6);
+        assert_eq!((-a).value(), 2);
+        assert_eq!((a / b) * b, a);
+        assert_eq!(a.pow(6).value(), 1);
+        assert_eq!(DynModInt::new(4, 10).inverse(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn dyn_modint_mismatched_moduli() {
+        let _ = DynModInt::new(1, 7) + DynModInt::new(1, 11);
+    }
+
+    #[test]
+    fn montgomery_matches_mul_mod() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let m = rng.next_u64() | 1;
+            let mont = Montgomery::new(m);
+            let (a, b) = (rng.next_u64() % m, rng.next_u64() % m);
+            let product = mont.mul(mont.to_montgomery(a), mont.to_montgomery(b));
+            assert_eq!(mont.from_montgomery(product), mul_mod(a, b, m));
+        }
+    }
+
+    #[test]
+    fn montgomery_pow() {
+        let mont = Montgomery::new(1_000_000_007);
+        assert_eq!(mont.pow(2, 1_000_000_006), 1);
+        assert_eq!(mont.pow(3, 0), 1);
+        assert_eq!(Montgomery::new(u64::MAX).pow(2, 64), 1);
+        assert_eq!(Montgomery::new(1).pow(5, 3), 0);
+    }
+}
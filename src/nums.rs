@@ -1,28 +1,446 @@
-use std::ops::Div;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::ops::{Add, Div, Mul, Rem, Sub};
+use std::str::FromStr;
 
-pub fn gcd(a: u64, b: u64) -> u64 {
-    let (mut a, mut b) = match (a, b) {
-        (a, b) if a < b => (b, a),
-        otherswise => otherswise,
+pub use crate::factorization::{
+    carmichael_lambda, carmichael_table, mobius, mobius_table, omega, omega_table, phi, phi_table,
+    sigma_k, sigma_k_table,
+};
+
+/// Primitive integer operations needed by the generic number theory helpers.
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + FromStr
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    fn wrapping_rem(self, rhs: Self) -> Self;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn saturating_mul(self, rhs: Self) -> Self;
+    /// Absolute value, `None` when it does not fit (`MIN` of signed types).
+    fn checked_abs(self) -> Option<Self>;
+    fn from_i128(value: i128) -> Option<Self>;
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_integer {
+    (unsigned: $($t:ty),*) => {
+        $(impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+
+            fn wrapping_rem(self, rhs: Self) -> Self {
+                <$t>::wrapping_rem(self, rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+
+            fn saturating_mul(self, rhs: Self) -> Self {
+                <$t>::saturating_mul(self, rhs)
+            }
+
+            fn checked_abs(self) -> Option<Self> {
+                Some(self)
+            }
+
+            fn from_i128(value: i128) -> Option<Self> {
+                Self::try_from(value).ok()
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        })*
+    };
+    (signed: $($t:ty),*) => {
+        $(impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+
+            fn wrapping_rem(self, rhs: Self) -> Self {
+                <$t>::wrapping_rem(self, rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+
+            fn saturating_mul(self, rhs: Self) -> Self {
+                <$t>::saturating_mul(self, rhs)
+            }
+
+            fn checked_abs(self) -> Option<Self> {
+                <$t>::checked_abs(self)
+            }
+
+            fn from_i128(value: i128) -> Option<Self> {
+                Self::try_from(value).ok()
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        })*
+    };
+}
+
+impl_integer!(unsigned: u8, u16, u32, u64, u128, usize);
+impl_integer!(signed: i8, i16, i32, i64, i128, isize);
+
+/// Integers with a type twice as wide, where the product of any two of them fits.
+pub trait Widen: Integer {
+    type Wide: Integer;
+
+    fn widen(self) -> Self::Wide;
+}
+
+macro_rules! impl_widen {
+    ($($t:ty => $wide:ty),*) => {
+        $(impl Widen for $t {
+            type Wide = $wide;
+
+            fn widen(self) -> $wide {
+                self as $wide
+            }
+        })*
     };
-    while b != 0 {
+}
+
+impl_widen!(u8 => u16, u16 => u32, u32 => u64, u64 => u128);
+impl_widen!(i8 => i16, i16 => i32, i32 => i64, i64 => i128);
+
+/// Greatest common divisor, always non-negative. `None` only when the result is `|MIN|` of a
+/// signed type (e.g. `checked_gcd(i64::MIN, 0)`), which is not representable.
+pub fn checked_gcd<T: Integer>(a: T, b: T) -> Option<T> {
+    let (mut a, mut b) = (a, b);
+    while b != T::ZERO {
         let tmp = b;
-        b = a % b;
+        // `MIN % -1` overflows, but its remainder is 0 anyway
+        b = a.wrapping_rem(b);
         a = tmp;
     }
-    a
+    a.checked_abs()
+}
+
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    checked_gcd(a, b).expect("gcd does not fit in the integer type")
+}
+
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    checked_lcm(a, b).expect("lcm does not fit in the integer type")
+}
+
+/// `None` if the least common multiple does not fit in `T`.
+pub fn checked_lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+    (a / checked_gcd(a, b)?).checked_mul(b)?.checked_abs()
+}
+
+/// Least common multiple clamped to `T::MAX`.
+pub fn saturating_lcm<T: Integer>(a: T, b: T) -> T {
+    checked_lcm(a, b).unwrap_or(T::MAX)
+}
+
+/// Least common multiple of two `u64`, which always fits in a `u128`.
+pub fn widening_lcm(a: u64, b: u64) -> u128 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    (a / gcd(a, b)) as u128 * b as u128
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LcmOverflow<T> {
+    /// Position of the element that made the running lcm overflow.
+    pub index: usize,
+    pub value: T,
+    /// Lcm of all the elements before `index`.
+    pub accumulated: T,
+}
+
+impl<T: Display> Display for LcmOverflow<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "lcm overflows at index {}: lcm({}, {}) does not fit",
+            self.index, self.accumulated, self.value
+        )
+    }
+}
+
+impl<T: Debug + Display> Error for LcmOverflow<T> {}
+
+pub fn checked_lcm_slice<T: Integer>(values: &[T]) -> Result<T, LcmOverflow<T>> {
+    values
+        .iter()
+        .enumerate()
+        .try_fold(T::ONE, |accumulated, (index, &value)| {
+            checked_lcm(accumulated, value).ok_or(LcmOverflow {
+                index,
+                value,
+                accumulated,
+            })
+        })
+}
+
+pub fn gcd_all<T: Integer>(values: impl IntoIterator<Item = T>) -> T {
+    values.into_iter().fold(T::ZERO, gcd)
+}
+
+pub fn lcm_all<T: Integer>(values: impl IntoIterator<Item = T>) -> T {
+    values.into_iter().fold(T::ONE, lcm)
+}
+
+pub(crate) fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+/// `base^exp mod m` using `u128` intermediates, so any `u64` modulus works. Panics if `m` is 0.
+pub fn pow_mod(base: u64, mut exp: u64, m: u64) -> u64 {
+    assert!(m > 0, "modulus must be positive");
+    let mut base = base % m;
+    let mut res = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = mul_mod(res, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    res
+}
+
+/// Smallest `x` such that `g^x = h (mod m)`, using baby-step giant-step.
+/// `g` and `m` do not need to be coprime. Returns `None` if there is no such `x` or `m` is 0.
+pub fn discrete_log(g: u64, h: u64, m: u64) -> Option<u64> {
+    if m == 0 {
+        return None;
+    }
+    let (g, mut h, mut m) = (g % m, h % m, m);
+    // peel common factors of g and m until they are coprime, solving `scale * g^x = h`
+    let (mut scale, mut offset) = (1 % m, 0);
+    loop {
+        let d = gcd(g, m);
+        if d == 1 {
+            break;
+        }
+        if scale == h {
+            return Some(offset);
+        }
+        if h % d != 0 {
+            return None;
+        }
+        h /= d;
+        m /= d;
+        offset += 1;
+        scale = mul_mod(scale, g / d, m);
+    }
+    let n = isqrt(m) + 1;
+    // baby steps: h * g^q for q in 0..=n, keeping the largest q for the smallest answer
+    let mut baby_steps: HashMap<u64, u64> = HashMap::new();
+    let mut current = h % m;
+    for q in 0..=n {
+        baby_steps.insert(current, q);
+        current = mul_mod(current, g, m);
+    }
+    // giant steps: scale * g^(n * p) for p in 1..=n
+    let giant = pow_mod(g, n, m);
+    let mut current = scale;
+    for p in 1..=n {
+        current = mul_mod(current, giant, m);
+        if let Some(&q) = baby_steps.get(&current) {
+            // n * p can pass u64::MAX when m is close to it, but the first hit is the smallest
+            // exponent, which is below the order of g and so below m
+            let x = n as u128 * p as u128 - q as u128 + offset as u128;
+            return Some(x as u64);
+        }
+    }
+    None
+}
+
+/// Returns `(g, x, y)` such that `g = gcd(a, b) = a * x + b * y`.
+pub fn extended_gcd(a: u64, b: u64) -> (u64, i128, i128) {
+    let (mut old_r, mut r) = (a as i128, b as i128);
+    let (mut old_x, mut x) = (1i128, 0i128);
+    let (mut old_y, mut y) = (0i128, 1i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    (old_r as u64, old_x, old_y)
+}
+
+/// Inverse of `a` modulo `m`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: u64, m: u64) -> Option<u64> {
+    if m == 0 {
+        return None;
+    }
+    match extended_gcd(a % m, m) {
+        (1, x, _) => Some(x.rem_euclid(m as i128) as u64),
+        _ => None,
+    }
+}
+
+/// Smallest solution and period of `a * x = b (mod m)`, if there is any.
+fn linear_congruence_base(a: u64, b: u64, m: u64) -> Option<(u64, u64)> {
+    if m == 0 {
+        return None;
+    }
+    let (a, b) = (a % m, b % m);
+    let g = gcd(a, m);
+    if b % g != 0 {
+        return None;
+    }
+    let (a, b, step) = (a / g, b / g, m / g);
+    let inverse = mod_inverse(a, step).expect("a / g and m / g are coprime");
+    Some((mul_mod(b, inverse, step), step))
+}
+
+/// All `x` in `0..m` such that `a * x = b (mod m)`, in increasing order.
+/// There are either none or exactly `gcd(a, m)` of them.
+pub fn solve_linear_congruence(a: u64, b: u64, m: u64) -> Vec<u64> {
+    match linear_congruence_base(a, b, m) {
+        Some((first, step)) => (0..m / step).map(|i| first + i * step).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Chinese remainder theorem over `(residue, modulus)` pairs, moduli need not be coprime.
+/// Returns `(x, l)` where `l` is the lcm of the moduli and `x < l` is the unique solution
+/// modulo `l`. `None` if the system is inconsistent, a modulus is 0 or `l` overflows `u128`.
+pub fn crt(congruences: &[(u64, u64)]) -> Option<(u128, u128)> {
+    congruences
+        .iter()
+        .try_fold((0u128, 1u128), |(x, modulus), &(residue, m)| {
+            if m == 0 {
+                return None;
+            }
+            // x + modulus * t = residue (mod m)
+            let (x_mod, modulus_mod) = ((x % m as u128) as u64, (modulus % m as u128) as u64);
+            let residue = residue % m;
+            let difference = if residue >= x_mod {
+                residue - x_mod
+            } else {
+                m - (x_mod - residue)
+            };
+            let (t, step) = linear_congruence_base(modulus_mod, difference, m)?;
+            // t < step, so the new x stays below the combined modulus once that one fits
+            let combined = modulus.checked_mul(step as u128)?;
+            Some((x + modulus * t as u128, combined))
+        })
+}
+
+/// `floor(n^(1/k))`, exact for every `u64`. Panics if `k` is 0.
+pub fn nth_root(n: u64, k: u32) -> u64 {
+    assert!(k > 0, "zeroth root is undefined");
+    if k == 1 || n < 2 {
+        return n;
+    }
+    if k >= 64 {
+        return 1;
+    }
+    // the float estimate can be off by a few units near the top of the range, fix it exactly
+    let mut root = (n as f64).powf(1.0 / k as f64) as u64;
+    while root.checked_pow(k).is_none_or(|power| power > n) {
+        root -= 1;
+    }
+    while (root + 1).checked_pow(k).is_some_and(|power| power <= n) {
+        root += 1;
+    }
+    root
+}
+
+pub fn isqrt(n: u64) -> u64 {
+    nth_root(n, 2)
+}
+
+/// `floor(sqrt(n))` for a `u128`, by Newton's method from a float estimate above the root.
+pub fn isqrt_u128(n: u128) -> u128 {
+    if let Ok(small) = u64::try_from(n) {
+        return isqrt(small) as u128;
+    }
+    // the estimate is within 2^12 of the root, start safely above it and descend
+    let mut root = (n as f64).sqrt() as u128 + (1 << 13);
+    loop {
+        let next = (root + n / root) / 2;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+pub fn icbrt(n: u64) -> u64 {
+    nth_root(n, 3)
+}
+
+pub fn is_perfect_square(n: u64) -> bool {
+    let root = isqrt(n);
+    root * root == n
+}
+
+/// `(base, exp)` with `base^exp = n`, `exp >= 2` and `exp` as large as possible.
+/// `None` if `n` is not a perfect power, including 0 and 1.
+pub fn perfect_power(n: u64) -> Option<(u64, u32)> {
+    if n < 4 {
+        return None;
+    }
+    (2..64 - n.leading_zeros()).rev().find_map(|k| {
+        let root = nth_root(n, k);
+        if root.pow(k) == n {
+            Some((root, k))
+        } else {
+            None
+        }
+    })
 }
 
-pub fn lcm(a: u64, b: u64) -> u64 {
-    match (a, b) {
-        (0, 0) => 0,
-        (a, b) => a.div(gcd(a, b)) * b,
+/// `floor(log_base(n))`, panics if `n` is 0 or `base` is smaller than 2.
+pub fn ilog(n: u64, base: u64) -> u32 {
+    assert!(n > 0, "logarithm of zero");
+    assert!(base >= 2, "logarithm base must be at least 2");
+    let (mut res, mut power) = (0, base);
+    while power <= n {
+        res += 1;
+        power = match power.checked_mul(base) {
+            Some(power) => power,
+            None => break,
+        };
     }
+    res
 }
 
 #[cfg(test)]
 mod test {
-    use crate::nums::{gcd, lcm};
+    use crate::nums::{
+        checked_gcd, checked_lcm, checked_lcm_slice, crt, discrete_log, extended_gcd, gcd, gcd_all,
+        icbrt, ilog, is_perfect_square, isqrt, isqrt_u128, lcm, lcm_all, mod_inverse, nth_root,
+        perfect_power, pow_mod, saturating_lcm, solve_linear_congruence, widening_lcm, LcmOverflow,
+    };
 
     #[test]
     fn test_gdc_example() {
@@ -33,8 +451,348 @@ mod test {
 
     #[test]
     fn test_lcm_example() {
-        let (a, b) = (761457, 614573);
+        let (a, b) = (761457u64, 614573u64);
         let expected = 467970912861;
         assert_eq!(lcm(a, b), expected);
     }
+
+    #[test]
+    fn test_gcd_every_primitive() {
+        assert_eq!(gcd(12u8, 18), 6);
+        assert_eq!(gcd(12u16, 18), 6);
+        assert_eq!(gcd(12u32, 18), 6);
+        assert_eq!(gcd(12u128, 18), 6);
+        assert_eq!(gcd(12usize, 18), 6);
+        assert_eq!(gcd(-12i8, 18), 6);
+        assert_eq!(gcd(12i16, -18), 6);
+        assert_eq!(gcd(-12i32, -18), 6);
+        assert_eq!(gcd(-12i128, 0), 12);
+        assert_eq!(gcd(0isize, -18), 18);
+    }
+
+    #[test]
+    fn test_gcd_signed_min() {
+        assert_eq!(gcd(i64::MIN, 6), 2);
+        assert_eq!(gcd(i64::MIN, -1), 1);
+        assert_eq!(gcd(i64::MIN, i64::MIN / 2), 1 << 62);
+        assert_eq!(checked_gcd(i64::MIN, 0), None);
+        assert_eq!(checked_gcd(i64::MIN, i64::MIN), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_gcd_signed_min_overflow() {
+        gcd(0, i64::MIN);
+    }
+
+    #[test]
+    fn test_lcm_signed() {
+        assert_eq!(lcm(-4i32, 6), 12);
+        assert_eq!(lcm(-4i64, -6), 12);
+        assert_eq!(lcm(0i8, -6), 0);
+        assert_eq!(lcm(0i8, 0), 0);
+        assert_eq!(lcm(i64::MIN, 0), 0);
+        assert_eq!(lcm(0, i64::MIN), 0);
+    }
+
+    #[test]
+    fn arithmetic_functions_reexported() {
+        use crate::nums::{carmichael_lambda, mobius, omega, phi, phi_table, sigma_k};
+        assert_eq!(phi(36), 12);
+        assert_eq!(mobius(30), -1);
+        assert_eq!(carmichael_lambda(561), 80);
+        assert_eq!(sigma_k(12, 1), Some(28));
+        assert_eq!(omega(360), 3);
+        assert_eq!(phi_table(6), vec![0, 1, 1, 2, 2, 4, 2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_lcm_overflow() {
+        lcm(18_446_744_073_709_551_557u64, 18_446_744_073_709_551_533);
+    }
+
+    #[test]
+    fn test_gcd_lcm_all() {
+        assert_eq!(gcd_all(vec![12u64, 18, 30]), 6);
+        assert_eq!(gcd_all(vec![-12i32, 18, -30]), 6);
+        assert_eq!(gcd_all(Vec::<u32>::new()), 0);
+        assert_eq!(lcm_all(vec![2usize, 3, 4, 5]), 60);
+        assert_eq!(lcm_all((1..=10).map(|i: i64| -i)), 2520);
+        assert_eq!(lcm_all(Vec::<u8>::new()), 1);
+    }
+
+    #[test]
+    fn extended_gcd_example() {
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+        assert_eq!(extended_gcd(0, 7), (7, 0, 1));
+        assert_eq!(extended_gcd(7, 0), (7, 1, 0));
+    }
+
+    #[test]
+    fn extended_gcd_full_range() {
+        let (a, b) = (u64::MAX, u64::MAX - 1);
+        let (g, x, y) = extended_gcd(a, b);
+        assert_eq!(g, 1);
+        assert_eq!(a as i128 * x + b as i128 * y, 1);
+    }
+
+    #[test]
+    fn mod_inverse_example() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(10, 17), Some(12));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_inverse(5, 1), Some(0));
+        assert_eq!(mod_inverse(5, 0), None);
+    }
+
+    #[test]
+    fn mod_inverse_big_modulus() {
+        let m = u64::MAX;
+        let a = u64::MAX - 2;
+        let inverse = mod_inverse(a, m).unwrap();
+        assert_eq!((a as u128 * inverse as u128 % m as u128) as u64, 1);
+    }
+
+    #[test]
+    fn linear_congruence_example() {
+        assert_eq!(solve_linear_congruence(14, 30, 100), vec![45, 95]);
+        assert_eq!(solve_linear_congruence(3, 2, 7), vec![3]);
+        assert_eq!(solve_linear_congruence(6, 5, 9), vec![]);
+        assert_eq!(solve_linear_congruence(0, 0, 3), vec![0, 1, 2]);
+        assert_eq!(solve_linear_congruence(4, 2, 0), vec![]);
+    }
+
+    #[test]
+    fn linear_congruence_brute_force() {
+        for m in 1..40u64 {
+            for a in 0..m {
+                for b in 0..m {
+                    let expected: Vec<u64> = (0..m).filter(|x| a * x % m == b).collect();
+                    assert_eq!(solve_linear_congruence(a, b, m), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn checked_lcm_large_primes() {
+        let (p, q) = (4_294_967_291u64, 4_294_967_279u64);
+        assert_eq!(checked_lcm(p, q), Some(p * q));
+        let (p, q) = (18_446_744_073_709_551_557u64, 18_446_744_073_709_551_533u64);
+        assert_eq!(checked_lcm(p, q), None);
+        assert_eq!(checked_lcm(p, p), Some(p));
+        assert_eq!(checked_lcm(0, p), Some(0));
+        assert_eq!(checked_lcm(i64::MIN, i64::MIN), None);
+        assert_eq!(checked_lcm(-4i8, 6), Some(12));
+    }
+
+    #[test]
+    fn saturating_and_widening_lcm() {
+        let (p, q) = (18_446_744_073_709_551_557u64, 18_446_744_073_709_551_533u64);
+        assert_eq!(saturating_lcm(p, q), u64::MAX);
+        assert_eq!(saturating_lcm(4u64, 6), 12);
+        assert_eq!(widening_lcm(p, q), p as u128 * q as u128);
+        assert_eq!(widening_lcm(4, 6), 12);
+        assert_eq!(widening_lcm(0, 0), 0);
+    }
+
+    #[test]
+    fn checked_lcm_slice_reports_index() {
+        assert_eq!(checked_lcm_slice(&[2u64, 3, 4, 5]), Ok(60));
+        assert_eq!(checked_lcm_slice::<u64>(&[]), Ok(1));
+        let values = [16u8, 3, 5, 7, 2];
+        assert_eq!(
+            checked_lcm_slice(&values),
+            Err(LcmOverflow {
+                index: 3,
+                value: 7,
+                accumulated: 240
+            })
+        );
+    }
+
+    #[test]
+    fn crt_example() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(7, 5)]), Some((2, 5)));
+        assert_eq!(crt(&[(1, 0)]), None);
+    }
+
+    #[test]
+    fn crt_non_coprime() {
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(3, 6), (3, 6), (1, 2)]), Some((3, 6)));
+    }
+
+    #[test]
+    fn crt_brute_force() {
+        for (m1, m2, m3) in [(4u64, 6u64, 9u64), (5, 10, 3), (8, 12, 18)] {
+            let l = lcm(lcm(m1, m2), m3);
+            for r1 in 0..m1 {
+                for r2 in 0..m2 {
+                    for r3 in 0..m3 {
+                        let expected = (0..l)
+                            .find(|x| x % m1 == r1 && x % m2 == r2 && x % m3 == r3)
+                            .map(|x| (x as u128, l as u128));
+                        assert_eq!(crt(&[(r1, m1), (r2, m2), (r3, m3)]), expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn crt_full_u64_moduli() {
+        let (p, q) = (18_446_744_073_709_551_557u64, 18_446_744_073_709_551_533u64);
+        let (x, l) = crt(&[(p - 1, p), (5, q)]).unwrap();
+        assert_eq!(l, p as u128 * q as u128);
+        assert_eq!(x % p as u128, (p - 1) as u128);
+        assert_eq!(x % q as u128, 5);
+        assert_eq!(crt(&[(0, p), (0, q), (0, p - 2)]), None);
+        assert_eq!(crt(&[(1, p), (0, q), (1, p - 2)]), None);
+    }
+
+    #[test]
+    fn pow_mod_example() {
+        assert_eq!(pow_mod(2, 10, 1000), 24);
+        assert_eq!(pow_mod(3, 0, 7), 1);
+        assert_eq!(pow_mod(3, 0, 1), 0);
+        assert_eq!(pow_mod(0, 0, 5), 1);
+        assert_eq!(pow_mod(u64::MAX - 1, u64::MAX, u64::MAX), u64::MAX - 1);
+        let p = 18_446_744_073_709_551_557;
+        assert_eq!(pow_mod(123_456_789, p - 1, p), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn pow_mod_zero_modulus() {
+        pow_mod(2, 3, 0);
+    }
+
+    #[test]
+    fn discrete_log_example() {
+        assert_eq!(discrete_log(2, 1, 7), Some(0));
+        assert_eq!(discrete_log(3, 13, 17), Some(4));
+        assert_eq!(discrete_log(2, 3, 7), None);
+        assert_eq!(discrete_log(5, 33, 58), Some(9));
+        assert_eq!(discrete_log(2, 0, 8), Some(3));
+        assert_eq!(discrete_log(7, 3, 1), Some(0));
+        assert_eq!(discrete_log(7, 3, 0), None);
+    }
+
+    #[test]
+    fn discrete_log_brute_force() {
+        for m in 1..60u64 {
+            for g in 0..m {
+                for h in 0..m {
+                    let expected = (0..2 * m).find(|&x| pow_mod(g, x, m) == h);
+                    assert_eq!(discrete_log(g, h, m), expected, "{} {} {}", g, h, m);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn discrete_log_large_prime() {
+        let (p, g) = (1_000_000_007, 5);
+        let x = 987_654_321;
+        assert_eq!(discrete_log(g, pow_mod(g, x, p), p), Some(x));
+    }
+
+    #[test]
+    fn integer_roots_example() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+        assert_eq!(icbrt(26), 2);
+        assert_eq!(icbrt(27), 3);
+        assert_eq!(icbrt(u64::MAX), 2_642_245);
+        assert_eq!(nth_root(1 << 63, 63), 2);
+        assert_eq!(nth_root(u64::MAX, 64), 1);
+        assert_eq!(nth_root(12345, 1), 12345);
+    }
+
+    #[test]
+    fn integer_roots_near_2_53() {
+        // from 2^53 on, f64 cannot tell a square apart from its neighbours
+        for root in [
+            94_906_265u64,
+            94_906_266,
+            134_217_727,
+            134_217_728,
+            4_294_967_295,
+        ] {
+            let square = root * root;
+            assert_eq!(isqrt(square), root);
+            assert_eq!(isqrt(square - 1), root - 1);
+            assert!(is_perfect_square(square));
+            assert!(!is_perfect_square(square - 1));
+            assert!(!is_perfect_square(square + 1));
+        }
+        for root in [208_063u64, 2_097_151, 2_642_245] {
+            let cube = root * root * root;
+            assert_eq!(icbrt(cube), root);
+            assert_eq!(icbrt(cube - 1), root - 1);
+        }
+    }
+
+    #[test]
+    fn isqrt_u128_boundaries() {
+        assert_eq!(isqrt_u128(u128::MAX), u64::MAX as u128);
+        assert_eq!(isqrt_u128(15), 3);
+        for root in [
+            1u128 << 32,
+            (1 << 40) + 7,
+            13_043_817_825_332_782_212,
+            u64::MAX as u128,
+        ] {
+            let square = root * root;
+            assert_eq!(isqrt_u128(square), root);
+            assert_eq!(isqrt_u128(square - 1), root - 1);
+            assert_eq!(isqrt_u128(square + 2 * root), root);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn zeroth_root() {
+        nth_root(5, 0);
+    }
+
+    #[test]
+    fn perfect_power_example() {
+        assert_eq!(perfect_power(64), Some((2, 6)));
+        assert_eq!(perfect_power(1 << 63), Some((2, 63)));
+        assert_eq!(perfect_power(3u64.pow(40)), Some((3, 40)));
+        assert_eq!(perfect_power(36), Some((6, 2)));
+        assert_eq!(
+            perfect_power(4_294_967_291 * 4_294_967_291),
+            Some((4_294_967_291, 2))
+        );
+        assert_eq!(perfect_power(72), None);
+        assert_eq!(perfect_power(u64::MAX), None);
+        assert_eq!(perfect_power(1), None);
+        assert_eq!(perfect_power(0), None);
+    }
+
+    #[test]
+    fn ilog_example() {
+        assert_eq!(ilog(1, 10), 0);
+        assert_eq!(ilog(999, 10), 2);
+        assert_eq!(ilog(1000, 10), 3);
+        assert_eq!(ilog(u64::MAX, 2), 63);
+        assert_eq!(ilog(u64::MAX, 10), 19);
+        assert_eq!(ilog(10_000_000_000_000_000_000, 10), 19);
+        assert_eq!(ilog(u64::MAX, u64::MAX), 1);
+        assert_eq!(ilog(u64::MAX - 1, u64::MAX), 0);
+        for n in 1..2000u64 {
+            assert_eq!(ilog(n, 3), n.ilog(3));
+        }
+    }
 }
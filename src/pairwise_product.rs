@@ -1,3 +1,7 @@
+use crate::nums::{Integer, Widen};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
 use std::ops::Mul;
 
 pub fn pairwise_product<N : Copy + Ord + Mul>(lst: &[N]) -> <N as Mul>::Output {
@@ -21,6 +25,170 @@ pub fn pairwise_product<N : Copy + Ord + Mul>(lst: &[N]) -> <N as Mul>::Output {
     *maxa * *maxb
 }
 
+/// Largest product of two elements when some of them may be negative, which is either the
+/// product of the two largest or of the two smallest.
+pub fn signed_pairwise_product<N: Copy + Ord + Mul<Output = N>>(lst: &[N]) -> N {
+    match best_pair(lst, |a, b| a * b) {
+        Ok(best) => best.product,
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// Largest product of `k` elements of `lst`, `None` if there are fewer than `k`.
+/// After sorting, the best choice takes pairs from either end, plus the largest element
+/// first when `k` is odd. Panics if the product overflows `N`.
+pub fn max_k_product<N: Integer>(lst: &[N], k: usize) -> Option<N> {
+    if k > lst.len() {
+        return None;
+    }
+    let mut sorted = lst.to_vec();
+    sorted.sort_unstable();
+    let (mut lo, mut hi) = (0, sorted.len());
+    let mut product = N::ONE;
+    let mut remaining = k;
+    if remaining % 2 == 1 {
+        let largest = sorted[hi - 1];
+        if largest < N::ZERO {
+            // an odd amount of negatives only: the product closest to zero is the best
+            return Some(sorted[hi - k..].iter().fold(N::ONE, |acc, &n| acc * n));
+        }
+        product = largest;
+        hi -= 1;
+        remaining -= 1;
+    }
+    while remaining > 0 {
+        let low_pair = sorted[lo] * sorted[lo + 1];
+        let high_pair = sorted[hi - 1] * sorted[hi - 2];
+        if low_pair > high_pair {
+            product = product * low_pair;
+            lo += 2;
+        } else {
+            product = product * high_pair;
+            hi -= 2;
+        }
+        remaining -= 2;
+    }
+    Some(product)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairwiseProductError {
+    /// Holds the length of the slice, which needs at least two elements.
+    NotEnoughElements(usize),
+    Overflow,
+}
+
+impl Display for PairwiseProductError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PairwiseProductError::NotEnoughElements(len) => {
+                write!(
+                    f,
+                    "pairwise product needs at least two elements, got {}",
+                    len
+                )
+            }
+            PairwiseProductError::Overflow => write!(f, "pairwise product overflows"),
+        }
+    }
+}
+
+impl Error for PairwiseProductError {}
+
+/// Largest pairwise product together with the positions of its two factors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PairwiseProduct<T> {
+    pub product: T,
+    pub indices: (usize, usize),
+}
+
+type IndexPair = (usize, usize);
+
+/// Indices of the two largest and of the two smallest elements, earliest first on ties.
+fn extreme_pairs<N: Ord>(lst: &[N]) -> Result<(IndexPair, IndexPair), PairwiseProductError> {
+    if lst.len() < 2 {
+        return Err(PairwiseProductError::NotEnoughElements(lst.len()));
+    }
+    let init = match lst[1].cmp(&lst[0]) {
+        Ordering::Greater => ((1, 0), (0, 1)),
+        Ordering::Equal => ((0, 1), (0, 1)),
+        Ordering::Less => ((0, 1), (1, 0)),
+    };
+    let pairs = (2..lst.len()).fold(init, |((max_a, max_b), (min_a, min_b)), i| {
+        let max = if lst[i] > lst[max_a] {
+            (i, max_a)
+        } else if lst[i] > lst[max_b] {
+            (max_a, i)
+        } else {
+            (max_a, max_b)
+        };
+        let min = if lst[i] < lst[min_a] {
+            (i, min_a)
+        } else if lst[i] < lst[min_b] {
+            (min_a, i)
+        } else {
+            (min_a, min_b)
+        };
+        (max, min)
+    });
+    Ok(pairs)
+}
+
+/// Picks the larger of the products of both candidate pairs, preferring the two largest.
+fn best_pair<N: Copy + Ord, T: Ord>(
+    lst: &[N],
+    product: impl Fn(N, N) -> T,
+) -> Result<PairwiseProduct<T>, PairwiseProductError> {
+    let (max, min) = extreme_pairs(lst)?;
+    let max_product = product(lst[max.0], lst[max.1]);
+    let min_product = product(lst[min.0], lst[min.1]);
+    Ok(if min_product > max_product {
+        PairwiseProduct {
+            product: min_product,
+            indices: min,
+        }
+    } else {
+        PairwiseProduct {
+            product: max_product,
+            indices: max,
+        }
+    })
+}
+
+/// Sign-aware largest pairwise product, failing if it does not fit in `N`.
+pub fn checked_pairwise_product<N: Integer>(
+    lst: &[N],
+) -> Result<PairwiseProduct<N>, PairwiseProductError> {
+    // an overflowing product is above every other one when its factors share a sign
+    // and below when they do not
+    let best = best_pair(lst, |a, b| match a.checked_mul(b) {
+        Some(product) => (0, product),
+        None if (a < N::ZERO) == (b < N::ZERO) => (1, N::ZERO),
+        None => (-1, N::ZERO),
+    })?;
+    match best.product {
+        (0, product) => Ok(PairwiseProduct {
+            product,
+            indices: best.indices,
+        }),
+        _ => Err(PairwiseProductError::Overflow),
+    }
+}
+
+/// Sign-aware largest pairwise product computed in the wider type, so it never overflows.
+pub fn widening_pairwise_product<N: Widen>(
+    lst: &[N],
+) -> Result<PairwiseProduct<N::Wide>, PairwiseProductError> {
+    best_pair(lst, |a, b| a.widen() * b.widen())
+}
+
+/// Sign-aware largest pairwise product, clamped to the range of `N`.
+pub fn saturating_pairwise_product<N: Integer>(
+    lst: &[N],
+) -> Result<PairwiseProduct<N>, PairwiseProductError> {
+    best_pair(lst, |a, b| a.saturating_mul(b))
+}
+
 #[cfg(test)]
 mod test {
     use crate::pairwise_product::*;
@@ -49,4 +217,190 @@ mod test {
             assert_eq!(pairwise_product(&example), naive_pairwise_product(&example));
         }
     }
+
+    #[test]
+    fn signed_example() {
+        assert_eq!(signed_pairwise_product(&[-10, -9, 1, 2]), 90);
+        assert_eq!(signed_pairwise_product(&[-10, 3, 1, 2]), 6);
+        assert_eq!(signed_pairwise_product(&[-1, -2]), 2);
+        assert_eq!(signed_pairwise_product(&[-5, 0, 4]), 0);
+        assert_eq!(signed_pairwise_product(&[-5, 4]), -20);
+    }
+
+    #[test]
+    fn signed_stress_test() {
+        use rand::prelude::*;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..1000 {
+            let length = 2 + rng.next_u32() as usize % 20;
+            let example: Vec<i64> = (0..length).map(|_| rng.gen_range(-1000..1000)).collect();
+            let mut expected = i64::MIN;
+            for i in 0..length {
+                for j in i + 1..length {
+                    expected = expected.max(example[i] * example[j]);
+                }
+            }
+            assert_eq!(signed_pairwise_product(&example), expected);
+        }
+    }
+
+    #[test]
+    fn max_k_product_example() {
+        assert_eq!(max_k_product(&[-10, -9, 1, 2], 2), Some(90));
+        assert_eq!(max_k_product(&[-10, -9, 1, 2], 3), Some(180));
+        assert_eq!(max_k_product(&[-3, -2, -1], 1), Some(-1));
+        assert_eq!(max_k_product(&[-3, -2, -1], 3), Some(-6));
+        assert_eq!(max_k_product(&[-3, -2, 0], 1), Some(0));
+        assert_eq!(max_k_product(&[-3, 0, 5], 2), Some(0));
+        assert_eq!(max_k_product(&[1u32, 2, 3], 0), Some(1));
+        assert_eq!(max_k_product(&[1u32, 2, 3], 4), None);
+    }
+
+    #[test]
+    fn max_k_product_stress_test() {
+        use rand::prelude::*;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..500 {
+            let length = 1 + rng.next_u32() as usize % 9;
+            let example: Vec<i64> = (0..length).map(|_| rng.gen_range(-10..10)).collect();
+            for k in 1..=length {
+                let expected = (0u32..1 << length)
+                    .filter(|mask| mask.count_ones() as usize == k)
+                    .map(|mask| {
+                        (0..length)
+                            .filter(|i| mask & (1 << i) != 0)
+                            .map(|i| example[i])
+                            .product::<i64>()
+                    })
+                    .max();
+                assert_eq!(max_k_product(&example, k), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn modes_example() {
+        let example = [3u64, 1 << 32, 7, 1 << 33];
+        assert_eq!(
+            checked_pairwise_product(&example),
+            Err(PairwiseProductError::Overflow)
+        );
+        assert_eq!(
+            widening_pairwise_product(&example),
+            Ok(PairwiseProduct {
+                product: 1 << 65,
+                indices: (3, 1)
+            })
+        );
+        assert_eq!(
+            saturating_pairwise_product(&example),
+            Ok(PairwiseProduct {
+                product: u64::MAX,
+                indices: (3, 1)
+            })
+        );
+        assert_eq!(
+            checked_pairwise_product(&[-10i32, 1, -9, 2]),
+            Ok(PairwiseProduct {
+                product: 90,
+                indices: (0, 2)
+            })
+        );
+        assert_eq!(
+            checked_pairwise_product(&[5u8, 5]),
+            Ok(PairwiseProduct {
+                product: 25,
+                indices: (0, 1)
+            })
+        );
+    }
+
+    #[test]
+    fn modes_not_enough_elements() {
+        let error = PairwiseProductError::NotEnoughElements(1);
+        assert_eq!(checked_pairwise_product(&[1i64]).unwrap_err(), error);
+        assert_eq!(saturating_pairwise_product(&[1i64]).unwrap_err(), error);
+        assert_eq!(widening_pairwise_product(&[1i64]).unwrap_err(), error);
+        assert_eq!(
+            widening_pairwise_product::<u8>(&[]).unwrap_err(),
+            PairwiseProductError::NotEnoughElements(0)
+        );
+    }
+
+    #[test]
+    fn extreme_pairs_earliest_on_ties() {
+        assert_eq!(extreme_pairs(&[5, 5]), Ok(((0, 1), (0, 1))));
+        assert_eq!(extreme_pairs(&[5, 5, 5]), Ok(((0, 1), (0, 1))));
+        assert_eq!(extreme_pairs(&[3, 3, 7, 1]), Ok(((2, 0), (3, 0))));
+    }
+
+    #[test]
+    fn checked_skips_negative_overflow() {
+        // i64::MIN * 2 overflows downwards, so it ranks below the fitting 3 * 2
+        let example = [i64::MIN, 2, 3];
+        assert_eq!(
+            checked_pairwise_product(&example),
+            Ok(PairwiseProduct {
+                product: 6,
+                indices: (2, 1)
+            })
+        );
+        // without another pair to fall back on the overflow is reported
+        assert_eq!(
+            checked_pairwise_product(&example[..2]),
+            Err(PairwiseProductError::Overflow)
+        );
+        assert_eq!(
+            checked_pairwise_product(&[i64::MIN + 1, -1, 2]),
+            Ok(PairwiseProduct {
+                product: i64::MAX,
+                indices: (0, 1)
+            })
+        );
+        assert_eq!(
+            saturating_pairwise_product(&[i64::MIN, 2, -1]),
+            Ok(PairwiseProduct {
+                product: i64::MAX,
+                indices: (0, 2)
+            })
+        );
+    }
+
+    #[test]
+    fn modes_stress_test() {
+        use rand::prelude::*;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..1000 {
+            let length = 2 + rng.next_u32() as usize % 20;
+            let example: Vec<i32> = (0..length)
+                .map(|_| rng.next_u32() as i32 >> (rng.next_u32() % 32))
+                .collect();
+            let mut expected = i64::MIN;
+            for i in 0..length {
+                for j in i + 1..length {
+                    expected = expected.max(example[i] as i64 * example[j] as i64);
+                }
+            }
+            let wide = widening_pairwise_product(&example).unwrap();
+            assert_eq!(wide.product, expected);
+            let (i, j) = wide.indices;
+            assert_ne!(i, j);
+            assert_eq!(example[i] as i64 * example[j] as i64, expected);
+            let saturated = expected.max(i32::MIN as i64).min(i32::MAX as i64) as i32;
+            assert_eq!(
+                saturating_pairwise_product(&example).unwrap().product,
+                saturated
+            );
+            match checked_pairwise_product(&example) {
+                Ok(checked) => assert_eq!(checked.product as i64, expected),
+                Err(error) => {
+                    assert_eq!(error, PairwiseProductError::Overflow);
+                    assert!(expected > i32::MAX as i64 || expected < i32::MIN as i64);
+                }
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,420 @@
+use crate::modint::Montgomery;
+use crate::nums::isqrt;
+use std::convert::TryFrom;
+
+/// Amount of numbers sieved at once by `SegmentedPrimes`.
+const SEGMENT_SIZE: u64 = 1 << 16;
+
+/// All primes `<= n`, using the sieve of Eratosthenes.
+pub fn sieve_of_eratosthenes(n: u64) -> Vec<u64> {
+    if n < 2 {
+        return Vec::new();
+    }
+    let n = n as usize;
+    let mut is_composite = vec![false; n + 1];
+    let mut i = 2;
+    while i * i <= n {
+        if !is_composite[i] {
+            for multiple in (i * i..=n).step_by(i) {
+                is_composite[multiple] = true;
+            }
+        }
+        i += 1;
+    }
+    (2..=n)
+        .filter(|&i| !is_composite[i])
+        .map(|i| i as u64)
+        .collect()
+}
+
+/// Linear sieve up to `n`, keeping the smallest prime factor of every number.
+pub struct LinearSieve {
+    smallest_factor: Vec<u32>,
+    primes: Vec<u64>,
+}
+
+impl LinearSieve {
+    pub fn new(n: u32) -> Self {
+        let n = n as usize;
+        let mut smallest_factor = vec![0u32; n + 1];
+        let mut primes: Vec<u64> = Vec::new();
+        for i in 2..=n {
+            if smallest_factor[i] == 0 {
+                smallest_factor[i] = i as u32;
+                primes.push(i as u64);
+            }
+            for &p in &primes {
+                let multiple = i * p as usize;
+                if p as u32 > smallest_factor[i] || multiple > n {
+                    break;
+                }
+                smallest_factor[multiple] = p as u32;
+            }
+        }
+        LinearSieve {
+            smallest_factor,
+            primes,
+        }
+    }
+
+    pub fn limit(&self) -> u32 {
+        (self.smallest_factor.len() - 1) as u32
+    }
+
+    pub fn primes(&self) -> &[u64] {
+        &self.primes
+    }
+
+    /// `None` for 0, 1 and numbers above the sieve limit.
+    pub fn smallest_prime_factor(&self, n: u32) -> Option<u32> {
+        match self.smallest_factor.get(n as usize) {
+            Some(&0) | None => None,
+            Some(&p) => Some(p),
+        }
+    }
+
+    pub fn is_prime(&self, n: u32) -> bool {
+        self.smallest_prime_factor(n) == Some(n)
+    }
+
+    /// Prime factors of `n` with their multiplicities, in increasing order.
+    /// Panics if `n` is above the sieve limit.
+    pub fn factorize(&self, mut n: u32) -> Vec<(u32, u32)> {
+        assert!(n <= self.limit(), "{} is above the sieve limit", n);
+        let mut res: Vec<(u32, u32)> = Vec::new();
+        while let Some(p) = self.smallest_prime_factor(n) {
+            match res.last_mut() {
+                Some((q, e)) if *q == p => *e += 1,
+                _ => res.push((p, 1)),
+            }
+            n /= p;
+        }
+        res
+    }
+}
+
+/// Lazy iterator over the primes of `[low, high]`, sieving one bounded segment at a time.
+pub struct SegmentedPrimes {
+    next_low: Option<u64>,
+    high: u64,
+    base_primes: Vec<u64>,
+    base_limit: u64,
+    segment: Vec<u64>,
+    position: usize,
+}
+
+impl SegmentedPrimes {
+    pub fn new(low: u64, high: u64) -> Self {
+        SegmentedPrimes {
+            next_low: Some(low.max(2)).filter(|&low| low <= high),
+            high,
+            base_primes: Vec::new(),
+            base_limit: 1,
+            segment: Vec::new(),
+            position: 0,
+        }
+    }
+
+    fn ensure_base_primes(&mut self, segment_high: u64) {
+        let needed = isqrt(segment_high);
+        if needed > self.base_limit {
+            self.base_limit = needed.max(self.base_limit.saturating_mul(2));
+            self.base_primes = sieve_of_eratosthenes(self.base_limit);
+        }
+    }
+
+    fn sieve_segment(&mut self, low: u64) {
+        let high = low.saturating_add(SEGMENT_SIZE - 1).min(self.high);
+        self.ensure_base_primes(high);
+        let mut is_composite = vec![false; (high - low + 1) as usize];
+        for &p in &self.base_primes {
+            let square = match p.checked_mul(p) {
+                Some(square) if square <= high => square,
+                _ => break,
+            };
+            let first = square.max(low.div_ceil(p) * p);
+            let mut multiple = first;
+            while multiple <= high {
+                is_composite[(multiple - low) as usize] = true;
+                multiple = match multiple.checked_add(p) {
+                    Some(next) => next,
+                    None => break,
+                };
+            }
+        }
+        self.segment = is_composite
+            .iter()
+            .enumerate()
+            .filter(|(_, &composite)| !composite)
+            .map(|(i, _)| low + i as u64)
+            .collect();
+        self.position = 0;
+        self.next_low = high.checked_add(1).filter(|&next| next <= self.high);
+    }
+}
+
+impl Iterator for SegmentedPrimes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.position == self.segment.len() {
+            let low = self.next_low?;
+            self.sieve_segment(low);
+        }
+        self.position += 1;
+        Some(self.segment[self.position - 1])
+    }
+}
+
+/// Primes in `[low, high]`, using memory proportional to `sqrt(high)`.
+pub fn segmented_sieve(low: u64, high: u64) -> SegmentedPrimes {
+    SegmentedPrimes::new(low, high)
+}
+
+/// All primes representable as `u64`, in increasing order.
+pub fn primes() -> SegmentedPrimes {
+    SegmentedPrimes::new(2, u64::MAX)
+}
+
+/// Miller-Rabin witnesses: the first 13 primes are enough for every `n < 3.3 * 10^24`.
+const WITNESSES: [u64; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+/// Deterministic Miller-Rabin test, correct for every `u64`.
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for &p in &WITNESSES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    let montgomery = Montgomery::new(n);
+    let one = montgomery.one();
+    let minus_one = montgomery.to_montgomery(n - 1);
+    'witness: for &a in &WITNESSES[..12] {
+        let mut x = montgomery.pow_montgomery(montgomery.to_montgomery(a), d);
+        if x == one || x == minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = montgomery.mul(x, x);
+            if x == minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+fn add_mod_u128(a: u128, b: u128, m: u128) -> u128 {
+    let (sum, overflow) = a.overflowing_add(b);
+    if overflow || sum >= m {
+        sum.wrapping_sub(m)
+    } else {
+        sum
+    }
+}
+
+fn mul_mod_u128(mut a: u128, mut b: u128, m: u128) -> u128 {
+    if let (Ok(a), Ok(b)) = (u64::try_from(a), u64::try_from(b)) {
+        return a as u128 * b as u128 % m;
+    }
+    a %= m;
+    let mut res = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            res = add_mod_u128(res, a, m);
+        }
+        a = add_mod_u128(a, a, m);
+        b >>= 1;
+    }
+    res
+}
+
+fn pow_mod_u128(mut base: u128, mut exp: u128, m: u128) -> u128 {
+    let mut res = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = mul_mod_u128(res, base, m);
+        }
+        base = mul_mod_u128(base, base, m);
+        exp >>= 1;
+    }
+    res
+}
+
+fn is_strong_probable_prime_u128(n: u128, a: u128) -> bool {
+    let s = (n - 1).trailing_zeros();
+    let mut x = pow_mod_u128(a % n, (n - 1) >> s, n);
+    if x == 1 || x == n - 1 {
+        return true;
+    }
+    for _ in 1..s {
+        x = mul_mod_u128(x, x, n);
+        if x == n - 1 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Miller-Rabin test for `u128`. It always checks the first 13 primes as witnesses, so it is
+/// deterministic below `3.3 * 10^24`; above that, `rounds` extra pseudo-random witnesses
+/// (derived from `n`, so results are reproducible) bound the error by `4^-rounds`.
+pub fn is_probable_prime(n: u128, rounds: u32) -> bool {
+    if let Ok(n) = u64::try_from(n) {
+        return is_prime(n);
+    }
+    if WITNESSES.iter().any(|&p| n.is_multiple_of(p as u128)) {
+        return false;
+    }
+    if !WITNESSES
+        .iter()
+        .all(|&a| is_strong_probable_prime_u128(n, a as u128))
+    {
+        return false;
+    }
+    // splitmix64
+    let mut state = (n as u64) ^ ((n >> 64) as u64);
+    (0..rounds).all(|_| {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        is_strong_probable_prime_u128(n, 2 + z as u128 % (n - 3))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::primes::{
+        is_prime, is_probable_prime, primes, segmented_sieve, sieve_of_eratosthenes, LinearSieve,
+    };
+
+    fn naive_is_prime(n: u64) -> bool {
+        n >= 2
+            && (2..)
+                .take_while(|d| d * d <= n)
+                .all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn eratosthenes_example() {
+        assert_eq!(
+            sieve_of_eratosthenes(30),
+            vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]
+        );
+        assert_eq!(sieve_of_eratosthenes(1), vec![]);
+        assert_eq!(sieve_of_eratosthenes(2), vec![2]);
+        assert_eq!(sieve_of_eratosthenes(1_000_000).len(), 78498);
+    }
+
+    #[test]
+    fn linear_sieve_smallest_factors() {
+        let sieve = LinearSieve::new(10_000);
+        assert_eq!(sieve.primes(), sieve_of_eratosthenes(10_000).as_slice());
+        assert_eq!(sieve.smallest_prime_factor(0), None);
+        assert_eq!(sieve.smallest_prime_factor(1), None);
+        assert_eq!(sieve.smallest_prime_factor(10_001), None);
+        for n in 2..=10_000u32 {
+            let p = sieve.smallest_prime_factor(n).unwrap();
+            assert_eq!(p, (2..=n).find(|d| n % d == 0).unwrap());
+        }
+    }
+
+    #[test]
+    fn linear_sieve_factorize() {
+        let sieve = LinearSieve::new(1000);
+        assert_eq!(sieve.factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(sieve.factorize(997), vec![(997, 1)]);
+        assert_eq!(sieve.factorize(1), vec![]);
+        assert!(sieve.is_prime(997));
+        assert!(!sieve.is_prime(999));
+    }
+
+    #[test]
+    fn segmented_matches_eratosthenes() {
+        let all = sieve_of_eratosthenes(300_000);
+        for &(low, high) in &[
+            (0, 300_000),
+            (1000, 200_000),
+            (65_530, 65_540),
+            (17, 17),
+            (20, 10),
+        ] {
+            let expected: Vec<u64> = all
+                .iter()
+                .cloned()
+                .filter(|p| (low..=high).contains(p))
+                .collect();
+            assert_eq!(segmented_sieve(low, high).collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn segmented_near_10_12() {
+        let (low, high) = (1_000_000_000_000, 1_000_000_000_500);
+        let expected: Vec<u64> = (low..=high).filter(|&n| naive_is_prime(n)).collect();
+        assert_eq!(segmented_sieve(low, high).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn lazy_primes() {
+        assert_eq!(primes().take(5).collect::<Vec<_>>(), vec![2, 3, 5, 7, 11]);
+        assert_eq!(primes().nth(10_000), Some(104_743));
+    }
+
+    #[test]
+    fn is_prime_matches_sieve() {
+        let sieve = LinearSieve::new(100_000);
+        for n in 0..=100_000u32 {
+            assert_eq!(is_prime(n as u64), sieve.is_prime(n), "{}", n);
+        }
+    }
+
+    #[test]
+    fn is_prime_large() {
+        assert!(is_prime(18_446_744_073_709_551_557));
+        assert!(!is_prime(u64::MAX));
+        assert!(is_prime(1_000_000_007));
+        // strong pseudoprimes to the bases 2 to 7, 2 to 13, 2 to 17 and 2 to 23
+        for &n in &[
+            3_215_031_751,
+            3_474_749_660_383,
+            341_550_071_728_321,
+            3_825_123_056_546_413_051,
+        ] {
+            assert!(!is_prime(n));
+        }
+        // product of the two largest primes below 2^32
+        assert!(!is_prime(4_294_967_291 * 4_294_967_279));
+    }
+
+    #[test]
+    fn is_prime_segment() {
+        let (low, high) = (1_000_000_000_000, 1_000_000_010_000);
+        let expected: Vec<u64> = segmented_sieve(low, high).collect();
+        let found: Vec<u64> = (low..=high).filter(|&n| is_prime(n)).collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn is_probable_prime_u128() {
+        // 2^89 - 1 and 2^127 - 1 are Mersenne primes
+        assert!(is_probable_prime((1 << 89) - 1, 10));
+        assert!(is_probable_prime((1 << 127) - 1, 10));
+        assert!(!is_probable_prime((1 << 127) + 1, 10));
+        assert!(!is_probable_prime(318_665_857_834_031_151_167_461, 0));
+        assert!(!is_probable_prime(
+            18_446_744_073_709_551_557u128 * 18_446_744_073_709_551_533,
+            10
+        ));
+        assert!(is_probable_prime(18_446_744_073_709_551_557, 0));
+        assert!(!is_probable_prime(u64::MAX as u128, 0));
+    }
+}
@@ -0,0 +1,406 @@
+use crate::nums::{gcd, lcm, Integer};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+/// Exact fraction `numer / denom`, always kept in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio<T> {
+    numer: T,
+    denom: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRatioError {
+    Invalid(String),
+    ZeroDenominator,
+}
+
+impl Display for ParseRatioError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRatioError::Invalid(s) => write!(f, "invalid rational number {:?}", s),
+            ParseRatioError::ZeroDenominator => write!(f, "zero denominator"),
+        }
+    }
+}
+
+impl Error for ParseRatioError {}
+
+/// Floor division, the remainder is always non-negative for a positive `b`.
+fn div_mod_floor<T: Integer>(a: T, b: T) -> (T, T) {
+    let (q, r) = (a / b, a % b);
+    if r < T::ZERO {
+        (q - T::ONE, r + b)
+    } else {
+        (q, r)
+    }
+}
+
+/// Compares `a / b` with `c / d` for positive `b` and `d` without any multiplication, by
+/// comparing integer parts and then the reciprocals of the fractional parts.
+fn cmp_fractions<T: Integer>(a: T, b: T, c: T, d: T) -> Ordering {
+    let (q1, r1) = div_mod_floor(a, b);
+    let (q2, r2) = div_mod_floor(c, d);
+    match (q1.cmp(&q2), r1 == T::ZERO, r2 == T::ZERO) {
+        (Ordering::Equal, true, true) => Ordering::Equal,
+        (Ordering::Equal, true, false) => Ordering::Less,
+        (Ordering::Equal, false, true) => Ordering::Greater,
+        (Ordering::Equal, false, false) => cmp_fractions(d, r2, b, r1),
+        (ordering, _, _) => ordering,
+    }
+}
+
+impl<T: Integer> Ratio<T> {
+    /// Panics if `denom` is zero.
+    pub fn new(numer: T, denom: T) -> Self {
+        assert!(denom != T::ZERO, "zero denominator");
+        let g = gcd(numer, denom);
+        let (numer, denom) = (numer / g, denom / g);
+        if denom < T::ZERO {
+            Ratio {
+                numer: T::ZERO - numer,
+                denom: T::ZERO - denom,
+            }
+        } else {
+            Ratio { numer, denom }
+        }
+    }
+
+    pub fn from_integer(value: T) -> Self {
+        Ratio {
+            numer: value,
+            denom: T::ONE,
+        }
+    }
+
+    pub fn numer(&self) -> T {
+        self.numer
+    }
+
+    pub fn denom(&self) -> T {
+        self.denom
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == T::ONE
+    }
+
+    /// Panics if `self` is zero.
+    pub fn recip(self) -> Self {
+        Ratio::new(self.denom, self.numer)
+    }
+
+    pub fn floor(&self) -> T {
+        div_mod_floor(self.numer, self.denom).0
+    }
+
+    pub fn ceil(&self) -> T {
+        match div_mod_floor(self.numer, self.denom) {
+            (q, r) if r == T::ZERO => q,
+            (q, _) => q + T::ONE,
+        }
+    }
+
+    /// Rounds to the nearest integer, halves away from zero.
+    pub fn round(&self) -> T {
+        let (q, r) = div_mod_floor(self.numer, self.denom);
+        match r.cmp(&(self.denom - r)) {
+            Ordering::Less => q,
+            Ordering::Greater => q + T::ONE,
+            Ordering::Equal if self.numer < T::ZERO => q,
+            Ordering::Equal => q + T::ONE,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numer.to_f64() / self.denom.to_f64()
+    }
+
+    /// Exact value of a finite `f64`, `None` if it does not fit in `T`.
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        if value == 0.0 {
+            return Some(Ratio::from_integer(T::ZERO));
+        }
+        // value = (-1)^sign * mantissa * 2^exponent
+        let bits = value.to_bits();
+        let sign = if bits >> 63 == 1 { -1i128 } else { 1 };
+        let biased_exponent = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = (bits & ((1 << 52) - 1)) as i128;
+        let (mut mantissa, mut exponent) = if biased_exponent == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | (1 << 52), biased_exponent - 1075)
+        };
+        let zeros = (mantissa.trailing_zeros() as i32).min(-exponent).max(0);
+        mantissa >>= zeros;
+        exponent += zeros;
+        if exponent >= 0 {
+            if exponent >= mantissa.leading_zeros() as i32 - 1 {
+                return None;
+            }
+            T::from_i128(sign * (mantissa << exponent)).map(Ratio::from_integer)
+        } else {
+            if -exponent >= 127 {
+                return None;
+            }
+            Some(Ratio {
+                numer: T::from_i128(sign * mantissa)?,
+                denom: T::from_i128(1 << -exponent)?,
+            })
+        }
+    }
+}
+
+impl<T: Integer> PartialOrd for Ratio<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Integer> Ord for Ratio<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_fractions(self.numer, self.denom, other.numer, other.denom)
+    }
+}
+
+impl<T: Integer> Add for Ratio<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let denom = lcm(self.denom, rhs.denom);
+        Ratio::new(
+            self.numer * (denom / self.denom) + rhs.numer * (denom / rhs.denom),
+            denom,
+        )
+    }
+}
+
+impl<T: Integer> Sub for Ratio<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let denom = lcm(self.denom, rhs.denom);
+        Ratio::new(
+            self.numer * (denom / self.denom) - rhs.numer * (denom / rhs.denom),
+            denom,
+        )
+    }
+}
+
+impl<T: Integer> Mul for Ratio<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        // cross reduce first to keep intermediates small
+        let g1 = gcd(self.numer, rhs.denom);
+        let g2 = gcd(rhs.numer, self.denom);
+        Ratio::new(
+            (self.numer / g1) * (rhs.numer / g2),
+            (self.denom / g2) * (rhs.denom / g1),
+        )
+    }
+}
+
+impl<T: Integer> Div for Ratio<T> {
+    type Output = Self;
+
+    /// Panics if `rhs` is zero.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.recip()
+    }
+}
+
+impl<T: Integer + Neg<Output = T>> Neg for Ratio<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Ratio {
+            numer: -self.numer,
+            denom: self.denom,
+        }
+    }
+}
+
+impl<T: Integer> From<T> for Ratio<T> {
+    fn from(value: T) -> Self {
+        Ratio::from_integer(value)
+    }
+}
+
+impl<T: Integer> Display for Ratio<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+impl<T: Integer> FromStr for Ratio<T> {
+    type Err = ParseRatioError;
+
+    /// Accepts `"a/b"`, plain integers and decimals such as `"-12.375"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseRatioError::Invalid(s.to_string());
+        let parse = |digits: &str| digits.parse::<T>().map_err(|_| invalid());
+        let s = s.trim();
+        if let Some((numer, denom)) = s.split_once('/') {
+            let (numer, denom) = (parse(numer.trim())?, parse(denom.trim())?);
+            if denom == T::ZERO {
+                return Err(ParseRatioError::ZeroDenominator);
+            }
+            return Ok(Ratio::new(numer, denom));
+        }
+        match s.split_once('.') {
+            Some((integer, decimals)) => {
+                if !decimals.chars().all(|c| c.is_ascii_digit())
+                    || !integer.chars().any(|c| c.is_ascii_digit()) && decimals.is_empty()
+                {
+                    return Err(invalid());
+                }
+                let numer = parse(&format!("{}{}", integer, decimals))?;
+                let denom = parse(&format!("1{}", "0".repeat(decimals.len())))?;
+                Ok(Ratio::new(numer, denom))
+            }
+            None => Ok(Ratio::from_integer(parse(s)?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ratio::{ParseRatioError, Ratio};
+
+    #[test]
+    fn normalization() {
+        let r = Ratio::new(6, -8);
+        assert_eq!((r.numer(), r.denom()), (-3, 4));
+        assert_eq!(Ratio::new(0, -5), Ratio::from_integer(0));
+        assert_eq!(Ratio::new(10u8, 4), Ratio::new(5, 2));
+        assert_eq!(Ratio::new(-4, -2).to_string(), "2");
+        assert_eq!(Ratio::new(1, -3).to_string(), "-1/3");
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_denominator() {
+        Ratio::new(1, 0);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let (a, b) = (Ratio::new(1i64, 6), Ratio::new(3i64, 4));
+        assert_eq!(a + b, Ratio::new(11, 12));
+        assert_eq!(a - b, Ratio::new(-7, 12));
+        assert_eq!(a * b, Ratio::new(1, 8));
+        assert_eq!(a / b, Ratio::new(2, 9));
+        assert_eq!(-a, Ratio::new(-1, 6));
+        assert_eq!(a * Ratio::from_integer(0), Ratio::from_integer(0));
+        // intermediates are reduced, so this does not overflow u8
+        assert_eq!(
+            Ratio::new(100u8, 101) * Ratio::new(101, 100),
+            Ratio::from(1)
+        );
+    }
+
+    #[test]
+    fn ordering_does_not_overflow() {
+        assert!(Ratio::new(199u8, 200) < Ratio::new(200, 201));
+        assert!(Ratio::new(-1i8, 127) > Ratio::new(-1, 126));
+        assert!(Ratio::new(i64::MAX, i64::MAX - 1) < Ratio::new(i64::MAX - 1, i64::MAX - 2));
+        assert_eq!(
+            Ratio::new(7, 3).cmp(&Ratio::new(14, 6)),
+            std::cmp::Ordering::Equal
+        );
+        let mut values = vec![
+            Ratio::new(1, 2),
+            Ratio::new(-1, 3),
+            Ratio::new(2, 3),
+            Ratio::from(0),
+        ];
+        values.sort();
+        assert_eq!(
+            values,
+            vec![
+                Ratio::new(-1, 3),
+                Ratio::from(0),
+                Ratio::new(1, 2),
+                Ratio::new(2, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn floor_ceil_round() {
+        let cases = [
+            ((7, 2), (3, 4, 4)),
+            ((-7, 2), (-4, -3, -4)),
+            ((5, 3), (1, 2, 2)),
+            ((-5, 3), (-2, -1, -2)),
+            ((4, 3), (1, 2, 1)),
+            ((-4, 3), (-2, -1, -1)),
+            ((6, 3), (2, 2, 2)),
+        ];
+        for &((n, d), (floor, ceil, round)) in &cases {
+            let r = Ratio::new(n, d);
+            assert_eq!(
+                (r.floor(), r.ceil(), r.round()),
+                (floor, ceil, round),
+                "{}",
+                r
+            );
+        }
+        assert_eq!(Ratio::new(255u8, 2).round(), 128);
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!("3/4".parse(), Ok(Ratio::new(3, 4)));
+        assert_eq!(" -6 / 8 ".parse(), Ok(Ratio::new(-3, 4)));
+        assert_eq!("42".parse(), Ok(Ratio::from(42)));
+        assert_eq!("-12.375".parse(), Ok(Ratio::new(-99, 8)));
+        assert_eq!("0.1".parse(), Ok(Ratio::new(1u32, 10)));
+        assert_eq!(".5".parse(), Ok(Ratio::new(1, 2)));
+        assert_eq!("3.".parse(), Ok(Ratio::from(3)));
+        assert_eq!(
+            "1/0".parse::<Ratio<i32>>(),
+            Err(ParseRatioError::ZeroDenominator)
+        );
+        assert!("1.2.3".parse::<Ratio<i32>>().is_err());
+        assert!("1.-2".parse::<Ratio<i32>>().is_err());
+        assert!(".".parse::<Ratio<i32>>().is_err());
+        assert!("a/b".parse::<Ratio<i32>>().is_err());
+        assert!("-1/2".parse::<Ratio<u32>>().is_err());
+    }
+
+    #[test]
+    fn f64_conversions() {
+        assert_eq!(Ratio::from_f64(0.75), Some(Ratio::new(3i64, 4)));
+        assert_eq!(Ratio::from_f64(-2.5), Some(Ratio::new(-5i32, 2)));
+        assert_eq!(
+            Ratio::from_f64(1e18),
+            Some(Ratio::from(1_000_000_000_000_000_000i64))
+        );
+        assert_eq!(Ratio::<i64>::from_f64(1e19), None);
+        assert_eq!(
+            Ratio::<u64>::from_f64(1e19),
+            Some(Ratio::from(10_000_000_000_000_000_000))
+        );
+        assert_eq!(Ratio::<u64>::from_f64(-1.0), None);
+        assert_eq!(Ratio::<i64>::from_f64(f64::NAN), None);
+        assert_eq!(Ratio::<i64>::from_f64(0.0), Some(Ratio::from(0)));
+        assert_eq!(Ratio::<i128>::from_f64(1e-300), None);
+        let third = Ratio::<i64>::from_f64(1.0 / 3.0).unwrap();
+        assert_eq!(third.to_f64(), 1.0 / 3.0);
+        assert_eq!(third.denom(), 1 << 54);
+        assert_eq!(Ratio::new(1, 8).to_f64(), 0.125);
+    }
+}
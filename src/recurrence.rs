@@ -0,0 +1,269 @@
+use crate::modint::DynModInt;
+use crate::primes::is_prime;
+
+/// Order-k linear recurrence
+/// `a(n) = coefficients[0] * a(n - 1) + ... + coefficients[k - 1] * a(n - k)`,
+/// starting from the terms `a(0), ..., a(k - 1)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearRecurrence {
+    coefficients: Vec<u64>,
+    initial: Vec<u64>,
+}
+
+impl LinearRecurrence {
+    /// Panics if there are not as many initial terms as coefficients.
+    pub fn new(coefficients: Vec<u64>, initial: Vec<u64>) -> Self {
+        assert_eq!(
+            coefficients.len(),
+            initial.len(),
+            "an order k recurrence needs k initial terms"
+        );
+        LinearRecurrence {
+            coefficients,
+            initial,
+        }
+    }
+
+    pub fn fibonacci() -> Self {
+        LinearRecurrence::new(vec![1, 1], vec![0, 1])
+    }
+
+    pub fn lucas() -> Self {
+        LinearRecurrence::new(vec![1, 1], vec![2, 1])
+    }
+
+    pub fn pell() -> Self {
+        LinearRecurrence::new(vec![2, 1], vec![0, 1])
+    }
+
+    pub fn tribonacci() -> Self {
+        LinearRecurrence::new(vec![1, 1, 1], vec![0, 0, 1])
+    }
+
+    pub fn order(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    pub fn coefficients(&self) -> &[u64] {
+        &self.coefficients
+    }
+
+    pub fn initial(&self) -> &[u64] {
+        &self.initial
+    }
+
+    /// `a(n) mod m` in `O(k^2 log n)` with Kitamasa's method: `x^n` is reduced modulo the
+    /// characteristic polynomial, and its coefficients weight the initial terms.
+    /// Panics if `m` is 0.
+    pub fn nth_mod(&self, n: u64, m: u64) -> u64 {
+        let k = self.order();
+        let zero = DynModInt::new(0, m);
+        if k == 0 {
+            return zero.value();
+        }
+        if n < k as u64 {
+            return self.initial[n as usize] % m;
+        }
+        let coefficients: Vec<DynModInt> = self
+            .coefficients
+            .iter()
+            .map(|&c| DynModInt::new(c, m))
+            .collect();
+        // uses x^k = coefficients[0] x^(k - 1) + ... + coefficients[k - 1] on the high terms
+        let reduce = |mut poly: Vec<DynModInt>| {
+            for degree in (k..poly.len()).rev() {
+                let top = poly[degree];
+                for (j, &c) in coefficients.iter().enumerate() {
+                    poly[degree - 1 - j] = poly[degree - 1 - j] + top * c;
+                }
+            }
+            poly.truncate(k);
+            poly
+        };
+
+        let mut power = vec![zero; k];
+        power[0] = DynModInt::new(1, m);
+        for bit in (0..64 - n.leading_zeros()).rev() {
+            let mut square = vec![zero; 2 * k - 1];
+            for (i, &a) in power.iter().enumerate() {
+                for (j, &b) in power.iter().enumerate() {
+                    square[i + j] = square[i + j] + a * b;
+                }
+            }
+            power = reduce(square);
+            if (n >> bit) & 1 == 1 {
+                power.insert(0, zero);
+                power = reduce(power);
+            }
+        }
+        power
+            .iter()
+            .zip(&self.initial)
+            .fold(zero, |acc, (&r, &a)| acc + r * DynModInt::new(a, m))
+            .value()
+    }
+}
+
+/// Shortest linear recurrence generating `sequence` over the integers mod the prime `p`,
+/// found with the Berlekamp-Massey algorithm. Panics if `p` is not prime.
+pub fn berlekamp_massey(sequence: &[u64], p: u64) -> LinearRecurrence {
+    assert!(is_prime(p), "modulus must be prime");
+    let zero = DynModInt::new(0, p);
+    let terms: Vec<DynModInt> = sequence.iter().map(|&s| DynModInt::new(s, p)).collect();
+    // connection polynomial c(x) = 1 - coefficients[0] x - ... - coefficients[k - 1] x^k
+    let mut current = vec![DynModInt::new(1, p)];
+    let mut previous = current.clone();
+    let (mut length, mut shift, mut previous_discrepancy) = (0, 1, DynModInt::new(1, p));
+    for n in 0..terms.len() {
+        let discrepancy = (1..=length).fold(terms[n], |acc, i| acc + current[i] * terms[n - i]);
+        if discrepancy == zero {
+            shift += 1;
+            continue;
+        }
+        let factor = discrepancy
+            * previous_discrepancy
+                .inverse()
+                .expect("nonzero values are invertible mod a prime");
+        let mut next = current.clone();
+        if next.len() < previous.len() + shift {
+            next.resize(previous.len() + shift, zero);
+        }
+        for (i, &b) in previous.iter().enumerate() {
+            next[i + shift] = next[i + shift] - factor * b;
+        }
+        if 2 * length <= n {
+            length = n + 1 - length;
+            previous = std::mem::replace(&mut current, next);
+            previous_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            current = next;
+            shift += 1;
+        }
+    }
+    current.resize(length + 1, zero);
+    LinearRecurrence::new(
+        current[1..].iter().map(|&c| (-c).value()).collect(),
+        terms[..length].iter().map(|t| t.value()).collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fibonacci::fibonacci_mod;
+    use crate::recurrence::{berlekamp_massey, LinearRecurrence};
+    use rand::RngCore;
+
+    fn naive(recurrence: &LinearRecurrence, count: usize, m: u64) -> Vec<u64> {
+        let k = recurrence.order();
+        let mut terms: Vec<u64> = recurrence.initial().iter().map(|a| a % m).collect();
+        while terms.len() < count {
+            let n = terms.len();
+            let next = (0..k).fold(0u128, |acc, j| {
+                (acc + recurrence.coefficients()[j] as u128 * terms[n - 1 - j] as u128) % m as u128
+            });
+            terms.push(next as u64);
+        }
+        terms.truncate(count);
+        terms
+    }
+
+    #[test]
+    fn presets_example() {
+        let m = u64::MAX;
+        let first =
+            |r: LinearRecurrence| -> Vec<u64> { (0..10).map(|n| r.nth_mod(n, m)).collect() };
+        assert_eq!(
+            first(LinearRecurrence::fibonacci()),
+            vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34]
+        );
+        assert_eq!(
+            first(LinearRecurrence::lucas()),
+            vec![2, 1, 3, 4, 7, 11, 18, 29, 47, 76]
+        );
+        assert_eq!(
+            first(LinearRecurrence::pell()),
+            vec![0, 1, 2, 5, 12, 29, 70, 169, 408, 985]
+        );
+        assert_eq!(
+            first(LinearRecurrence::tribonacci()),
+            vec![0, 0, 1, 1, 2, 4, 7, 13, 24, 44]
+        );
+    }
+
+    #[test]
+    fn nth_mod_matches_fibonacci() {
+        let fibonacci = LinearRecurrence::fibonacci();
+        for &n in &[0u64, 1, 50, 1_000_000_000_000_000_000, u64::MAX] {
+            for &m in &[1u64, 10, 1_000_000_007, u64::MAX] {
+                assert_eq!(fibonacci.nth_mod(n, m), fibonacci_mod(n, m));
+            }
+        }
+        // L(n) = F(n - 1) + F(n + 1)
+        let n = 123_456_789_012;
+        let m = 998_244_353;
+        let expected = (fibonacci_mod(n - 1, m) + fibonacci_mod(n + 1, m)) % m;
+        assert_eq!(LinearRecurrence::lucas().nth_mod(n, m), expected);
+    }
+
+    #[test]
+    fn nth_mod_matches_naive() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let k = 1 + rng.next_u32() as usize % 6;
+            let m = 1 + rng.next_u64() % 1_000_000;
+            let recurrence = LinearRecurrence::new(
+                (0..k).map(|_| rng.next_u64()).collect(),
+                (0..k).map(|_| rng.next_u64()).collect(),
+            );
+            let expected = naive(&recurrence, 60, m);
+            for (n, &term) in expected.iter().enumerate() {
+                assert_eq!(recurrence.nth_mod(n as u64, m), term);
+            }
+        }
+        assert_eq!(LinearRecurrence::new(vec![], vec![]).nth_mod(10, 7), 0);
+    }
+
+    #[test]
+    fn berlekamp_massey_example() {
+        let p = 1_000_000_007;
+        let fibonacci: Vec<u64> = (0..10).map(|n| fibonacci_mod(n, p)).collect();
+        assert_eq!(
+            berlekamp_massey(&fibonacci, p),
+            LinearRecurrence::fibonacci()
+        );
+        let powers: Vec<u64> = (0..8).map(|n| 3u64.pow(n)).collect();
+        assert_eq!(
+            berlekamp_massey(&powers, p),
+            LinearRecurrence::new(vec![3], vec![1])
+        );
+        assert_eq!(
+            berlekamp_massey(&[0, 0, 0], p),
+            LinearRecurrence::new(vec![], vec![])
+        );
+        assert_eq!(berlekamp_massey(&[], p).order(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn berlekamp_massey_composite_modulus() {
+        berlekamp_massey(&[0, 1, 1, 2, 3, 5], 15);
+    }
+
+    #[test]
+    fn berlekamp_massey_recovers_random_recurrences() {
+        let mut rng = rand::thread_rng();
+        let p = 998_244_353;
+        for _ in 0..50 {
+            let k = 1 + rng.next_u32() as usize % 8;
+            let recurrence = LinearRecurrence::new(
+                (0..k).map(|_| rng.next_u64() % p).collect(),
+                (0..k).map(|_| rng.next_u64() % p).collect(),
+            );
+            let prefix = naive(&recurrence, 2 * k, p);
+            let found = berlekamp_massey(&prefix, p);
+            assert!(found.order() <= k);
+            assert_eq!(naive(&found, 4 * k, p), naive(&recurrence, 4 * k, p));
+        }
+    }
+}
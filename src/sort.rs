@@ -33,6 +33,135 @@ where
     }
 }
 
+/// Slices up to this length are finished with insertion sort.
+const INSERTION_SORT_THRESHOLD: usize = 16;
+/// From this length the pivot is the median of three medians of three (Tukey's ninther).
+const NINTHER_THRESHOLD: usize = 128;
+
+fn insertion_sort<T: Ord>(values: &mut [T]) {
+    for i in 1..values.len() {
+        let mut j = i;
+        while j > 0 && values[j] < values[j - 1] {
+            values.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+fn sift_down<T: Ord>(values: &mut [T], mut root: usize) {
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= values.len() {
+            return;
+        }
+        if child + 1 < values.len() && values[child + 1] > values[child] {
+            child += 1;
+        }
+        if values[root] >= values[child] {
+            return;
+        }
+        values.swap(root, child);
+        root = child;
+    }
+}
+
+fn heap_sort<T: Ord>(values: &mut [T]) {
+    for root in (0..values.len() / 2).rev() {
+        sift_down(values, root);
+    }
+    for end in (1..values.len()).rev() {
+        values.swap(0, end);
+        sift_down(&mut values[..end], 0);
+    }
+}
+
+fn median_of_three<T: Ord>(values: &[T], a: usize, b: usize, c: usize) -> usize {
+    if values[a] < values[b] {
+        if values[b] < values[c] {
+            b
+        } else if values[a] < values[c] {
+            c
+        } else {
+            a
+        }
+    } else if values[a] < values[c] {
+        a
+    } else if values[b] < values[c] {
+        c
+    } else {
+        b
+    }
+}
+
+fn choose_pivot<T: Ord>(values: &[T]) -> usize {
+    let (len, middle) = (values.len(), values.len() / 2);
+    if len < NINTHER_THRESHOLD {
+        return median_of_three(values, 0, middle, len - 1);
+    }
+    let step = len / 8;
+    let first = median_of_three(values, 0, step, 2 * step);
+    let second = median_of_three(values, middle - step, middle, middle + step);
+    let third = median_of_three(values, len - 1 - 2 * step, len - 1 - step, len - 1);
+    median_of_three(values, first, second, third)
+}
+
+/// Dijkstra's 3-way partition around `values[0]`, returning the bounds `(lt, gt)` of the run
+/// equal to the pivot: `values[..lt]` is smaller and `values[gt..]` is greater.
+fn partition<T: Ord>(values: &mut [T]) -> (usize, usize) {
+    let (mut lt, mut i, mut gt) = (0, 1, values.len());
+    // the pivot always sits at values[lt], the start of the equal run
+    while i < gt {
+        match values[i].cmp(&values[lt]) {
+            Ordering::Less => {
+                values.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                values.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    (lt, gt)
+}
+
+fn introsort<T: Ord>(mut values: &mut [T], mut depth_limit: u32) {
+    loop {
+        if values.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort(values);
+            return;
+        }
+        if depth_limit == 0 {
+            heap_sort(values);
+            return;
+        }
+        depth_limit -= 1;
+        let pivot = choose_pivot(values);
+        values.swap(0, pivot);
+        let (lt, gt) = partition(values);
+        // recurse into the smaller side and loop on the larger to keep the stack logarithmic
+        let (lower, rest) = values.split_at_mut(lt);
+        let higher = &mut rest[gt - lt..];
+        if lower.len() < higher.len() {
+            introsort(lower, depth_limit);
+            values = higher;
+        } else {
+            introsort(higher, depth_limit);
+            values = lower;
+        }
+    }
+}
+
+/// In-place introsort: quicksort with median-of-three or ninther pivots and 3-way partitioning,
+/// insertion sort for short slices, and heapsort once the recursion goes deeper than
+/// `2 * log2(n)`, so it is `O(n log n)` in the worst case. Not stable.
+pub fn quick_sort<T: Ord>(values: &mut [T]) {
+    let depth_limit = 2 * (usize::BITS - values.len().leading_zeros());
+    introsort(values, depth_limit);
+}
+
 fn merge<T>(s1: &[T], s2: &[T]) -> Vec<T>
 where
     T: Copy + Ord,
@@ -148,7 +277,18 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::sort::{merge, merge_sort, number_of_inversions, quick_sorted};
+    use crate::sort::{
+        heap_sort, merge, merge_sort, number_of_inversions, quick_sort, quick_sorted,
+    };
+    use rand::RngCore;
+
+    fn assert_quick_sort(values: Vec<u64>) {
+        let mut expected = values.clone();
+        expected.sort();
+        let mut sorted = values;
+        quick_sort(&mut sorted);
+        assert_eq!(sorted, expected);
+    }
 
     #[test]
     fn test_quicksort_example() {
@@ -184,4 +324,51 @@ mod test {
         let s = [9, 8, 7, 3, 2, 1];
         assert_eq!(number_of_inversions(&s), (vec![1, 2, 3, 7, 8, 9], 15));
     }
+
+    #[test]
+    fn quick_sort_example() {
+        let mut s = [2, 3, 9, 2, 2];
+        quick_sort(&mut s);
+        assert_eq!(s.to_vec(), quick_sorted(&[2, 3, 9, 2, 2]));
+        let mut empty: [u8; 0] = [];
+        quick_sort(&mut empty);
+        let mut words = vec!["pear".to_string(), "apple".to_string(), "fig".to_string()];
+        quick_sort(&mut words);
+        assert_eq!(words, vec!["apple", "fig", "pear"]);
+    }
+
+    #[test]
+    fn quick_sort_random() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let length = rng.next_u32() as usize % 1000;
+            let modulus = 1 + rng.next_u64() % 1000;
+            assert_quick_sort((0..length).map(|_| rng.next_u64() % modulus).collect());
+        }
+    }
+
+    #[test]
+    fn quick_sort_adversarial() {
+        let n = 100_000u64;
+        assert_quick_sort((0..n).collect());
+        assert_quick_sort((0..n).rev().collect());
+        assert_quick_sort(vec![7; n as usize]);
+        assert_quick_sort((0..n).map(|i| i % 2).collect());
+        // organ pipe
+        assert_quick_sort((0..n / 2).chain((0..n / 2).rev()).collect());
+        // sorted with the largest values first
+        assert_quick_sort((n / 2..n).chain(0..n / 2).collect());
+    }
+
+    #[test]
+    fn heap_sort_random() {
+        let mut rng = rand::thread_rng();
+        for length in 0..100 {
+            let mut values: Vec<u64> = (0..length).map(|_| rng.next_u64() % 50).collect();
+            let mut expected = values.clone();
+            expected.sort();
+            heap_sort(&mut values);
+            assert_eq!(values, expected);
+        }
+    }
 }
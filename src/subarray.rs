@@ -0,0 +1,313 @@
+use crate::nums::Integer;
+
+/// Best contiguous run `lst[start..end]` and its sum or product.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subarray<N> {
+    pub value: N,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Best rectangle `grid[top..bottom][left..right]` and its sum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rectangle<N> {
+    pub sum: N,
+    pub top: usize,
+    pub bottom: usize,
+    pub left: usize,
+    pub right: usize,
+}
+
+/// Non-empty subarray with the largest sum using Kadane's algorithm, `None` for an empty slice.
+/// On ties the subarray ending first wins, and among those the shortest one.
+pub fn max_subarray_sum<N: Integer>(lst: &[N]) -> Option<Subarray<N>> {
+    let first = *lst.first()?;
+    let mut best = Subarray {
+        value: first,
+        start: 0,
+        end: 1,
+    };
+    // best subarray ending at the current position
+    let (mut current, mut current_start) = (first, 0);
+    for (i, &n) in lst.iter().enumerate().skip(1) {
+        if current > N::ZERO {
+            current = current + n;
+        } else {
+            current = n;
+            current_start = i;
+        }
+        if current > best.value {
+            best = Subarray {
+                value: current,
+                start: current_start,
+                end: i + 1,
+            };
+        }
+    }
+    Some(best)
+}
+
+fn max_crossing_sum<N: Integer>(lst: &[N], middle: usize) -> Subarray<N> {
+    let (mut left_sum, mut left) = (lst[middle - 1], middle - 1);
+    let mut sum = left_sum;
+    for i in (0..middle - 1).rev() {
+        sum = sum + lst[i];
+        if sum > left_sum {
+            left_sum = sum;
+            left = i;
+        }
+    }
+    let (mut right_sum, mut right) = (lst[middle], middle + 1);
+    let mut sum = right_sum;
+    for (i, &n) in lst.iter().enumerate().skip(middle + 1) {
+        sum = sum + n;
+        if sum > right_sum {
+            right_sum = sum;
+            right = i + 1;
+        }
+    }
+    Subarray {
+        value: left_sum + right_sum,
+        start: left,
+        end: right,
+    }
+}
+
+fn divide_conquer<N: Integer>(lst: &[N], offset: usize) -> Subarray<N> {
+    if lst.len() == 1 {
+        return Subarray {
+            value: lst[0],
+            start: offset,
+            end: offset + 1,
+        };
+    }
+    let middle = lst.len() / 2;
+    let left = divide_conquer(&lst[..middle], offset);
+    let right = divide_conquer(&lst[middle..], offset + middle);
+    let mut crossing = max_crossing_sum(lst, middle);
+    crossing.start += offset;
+    crossing.end += offset;
+    [crossing, right].iter().fold(left, |best, &candidate| {
+        if candidate.value > best.value {
+            candidate
+        } else {
+            best
+        }
+    })
+}
+
+/// Same result as `max_subarray_sum` in `O(n log n)`, splitting the slice in halves and
+/// combining with the best subarray crossing the middle. The chosen indices may differ on ties.
+pub fn max_subarray_sum_divide_conquer<N: Integer>(lst: &[N]) -> Option<Subarray<N>> {
+    if lst.is_empty() {
+        return None;
+    }
+    Some(divide_conquer(lst, 0))
+}
+
+/// Non-empty subarray with the largest product, `None` for an empty slice. Both the largest and
+/// the smallest products ending at each position are tracked, since a negative factor swaps them.
+pub fn max_product_subarray<N: Integer>(lst: &[N]) -> Option<Subarray<N>> {
+    let first = *lst.first()?;
+    let mut best = Subarray {
+        value: first,
+        start: 0,
+        end: 1,
+    };
+    let (mut max, mut min) = ((first, 0), (first, 0));
+    for (i, &n) in lst.iter().enumerate().skip(1) {
+        let candidates = [(n, i), (max.0 * n, max.1), (min.0 * n, min.1)];
+        max = candidates
+            .iter()
+            .cloned()
+            .fold(candidates[0], |a, b| if b.0 > a.0 { b } else { a });
+        min = candidates
+            .iter()
+            .cloned()
+            .fold(candidates[0], |a, b| if b.0 < a.0 { b } else { a });
+        if max.0 > best.value {
+            best = Subarray {
+                value: max.0,
+                start: max.1,
+                end: i + 1,
+            };
+        }
+    }
+    Some(best)
+}
+
+/// Non-empty rectangle with the largest sum in `O(rows^2 * cols)`, running Kadane's algorithm
+/// on the column sums of every band of rows. `None` if the grid is empty.
+/// Panics if the rows have different lengths.
+pub fn max_sum_rectangle<N: Integer>(grid: &[Vec<N>]) -> Option<Rectangle<N>> {
+    let cols = grid.first()?.len();
+    assert!(grid.iter().all(|row| row.len() == cols), "ragged rows");
+    let mut best: Option<Rectangle<N>> = None;
+    for top in 0..grid.len() {
+        let mut column_sums = vec![N::ZERO; cols];
+        for (bottom, row) in grid.iter().enumerate().skip(top) {
+            for (sum, &n) in column_sums.iter_mut().zip(row) {
+                *sum = *sum + n;
+            }
+            if let Some(band) = max_subarray_sum(&column_sums) {
+                if best.is_none_or(|best| band.value > best.sum) {
+                    best = Some(Rectangle {
+                        sum: band.value,
+                        top,
+                        bottom: bottom + 1,
+                        left: band.start,
+                        right: band.end,
+                    });
+                }
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use crate::subarray::{
+        max_product_subarray, max_subarray_sum, max_subarray_sum_divide_conquer, max_sum_rectangle,
+        Rectangle, Subarray,
+    };
+    use rand::Rng;
+
+    fn naive_best(lst: &[i64], combine: fn(i64, i64) -> i64) -> Option<i64> {
+        (0..lst.len())
+            .flat_map(|start| {
+                (start + 1..=lst.len()).map(move |end| {
+                    lst[start + 1..end]
+                        .iter()
+                        .fold(lst[start], |a, &b| combine(a, b))
+                })
+            })
+            .max()
+    }
+
+    #[test]
+    fn max_subarray_sum_example() {
+        let example = [-2, 1, -3, 4, -1, 2, 1, -5, 4];
+        let expected = Some(Subarray {
+            value: 6,
+            start: 3,
+            end: 7,
+        });
+        assert_eq!(max_subarray_sum(&example), expected);
+        assert_eq!(max_subarray_sum_divide_conquer(&example), expected);
+        assert_eq!(max_subarray_sum(&[-3, -1, -2]).unwrap().value, -1);
+        assert_eq!(max_subarray_sum::<i32>(&[]), None);
+        assert_eq!(max_subarray_sum_divide_conquer::<i32>(&[]), None);
+    }
+
+    #[test]
+    fn max_subarray_sum_ties() {
+        let found = |lst: &[i32]| {
+            let best = max_subarray_sum(lst).unwrap();
+            (best.value, best.start, best.end)
+        };
+        assert_eq!(found(&[0, 5]), (5, 1, 2));
+        assert_eq!(found(&[5, 0]), (5, 0, 1));
+        assert_eq!(found(&[2, -2, 2]), (2, 0, 1));
+        assert_eq!(found(&[-1, 3, -3, 3]), (3, 1, 2));
+    }
+
+    #[test]
+    fn max_product_subarray_example() {
+        let expected = Some(Subarray {
+            value: 6,
+            start: 0,
+            end: 2,
+        });
+        assert_eq!(max_product_subarray(&[2, 3, -2, 4]), expected);
+        assert_eq!(max_product_subarray(&[-2, 0, -1]).unwrap().value, 0);
+        let found = max_product_subarray(&[-2, 3, -4]).unwrap();
+        assert_eq!((found.value, found.start, found.end), (24, 0, 3));
+        assert_eq!(max_product_subarray(&[3u32, 0, 2, 2]).unwrap().value, 4);
+        assert_eq!(max_product_subarray::<i8>(&[]), None);
+    }
+
+    #[test]
+    fn max_sum_rectangle_example() {
+        let grid = vec![
+            vec![1, 2, -1, -4, -20],
+            vec![-8, -3, 4, 2, 1],
+            vec![3, 8, 10, 1, 3],
+            vec![-4, -1, 1, 7, -6],
+        ];
+        assert_eq!(
+            max_sum_rectangle(&grid),
+            Some(Rectangle {
+                sum: 29,
+                top: 1,
+                bottom: 4,
+                left: 1,
+                right: 4,
+            })
+        );
+        assert_eq!(max_sum_rectangle::<i32>(&[]), None);
+        assert_eq!(max_sum_rectangle::<i32>(&[vec![]]), None);
+    }
+
+    #[test]
+    fn stress_test() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..500 {
+            let length = rng.gen_range(1..30);
+            let example: Vec<i64> = (0..length).map(|_| rng.gen_range(-20..20)).collect();
+            let expected = naive_best(&example, |a, b| a + b).unwrap();
+            for found in [
+                max_subarray_sum(&example).unwrap(),
+                max_subarray_sum_divide_conquer(&example).unwrap(),
+            ] {
+                assert_eq!(found.value, expected);
+                assert_eq!(
+                    example[found.start..found.end].iter().sum::<i64>(),
+                    expected
+                );
+            }
+
+            let small: Vec<i64> = example.iter().map(|n| n % 4).take(12).collect();
+            let expected = naive_best(&small, |a, b| a * b).unwrap();
+            let found = max_product_subarray(&small).unwrap();
+            assert_eq!(found.value, expected);
+            assert_eq!(
+                small[found.start..found.end].iter().product::<i64>(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn rectangle_stress_test() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let (rows, cols) = (rng.gen_range(1..7), rng.gen_range(1..7));
+            let grid: Vec<Vec<i64>> = (0..rows)
+                .map(|_| (0..cols).map(|_| rng.gen_range(-10..10)).collect())
+                .collect();
+            let sum = |top: usize, bottom: usize, left: usize, right: usize| -> i64 {
+                grid[top..bottom]
+                    .iter()
+                    .map(|row| row[left..right].iter().sum::<i64>())
+                    .sum()
+            };
+            let mut expected = i64::MIN;
+            for top in 0..rows {
+                for bottom in top + 1..=rows {
+                    for left in 0..cols {
+                        for right in left + 1..=cols {
+                            expected = expected.max(sum(top, bottom, left, right));
+                        }
+                    }
+                }
+            }
+            let found = max_sum_rectangle(&grid).unwrap();
+            assert_eq!(found.sum, expected);
+            assert_eq!(
+                sum(found.top, found.bottom, found.left, found.right),
+                expected
+            );
+        }
+    }
+}
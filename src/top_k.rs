@@ -0,0 +1,287 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// Value ordered by its key, and among equal keys the one inserted later is smaller.
+struct Keyed<K, T> {
+    key: K,
+    order: u64,
+    value: T,
+}
+
+impl<K: Ord, T> PartialEq for Keyed<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.order == other.order
+    }
+}
+
+impl<K: Ord, T> Eq for Keyed<K, T> {}
+
+impl<K: Ord, T> PartialOrd for Keyed<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, T> Ord for Keyed<K, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key
+            .cmp(&other.key)
+            .then_with(|| other.order.cmp(&self.order))
+    }
+}
+
+/// Streaming accumulator of the `k` values with the largest keys, backed by a min-heap of
+/// size `k` so each value costs `O(log k)`. On ties the values seen first are kept.
+pub struct TopK<T, K, F> {
+    k: usize,
+    key: F,
+    // number of values inserted so far, to order ties
+    inserted: u64,
+    heap: BinaryHeap<Reverse<Keyed<K, T>>>,
+}
+
+impl<T: Ord + Clone> TopK<T, T, fn(&T) -> T> {
+    /// Keeps the `k` largest values.
+    pub fn new(k: usize) -> Self {
+        TopK::by_key(k, T::clone)
+    }
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> TopK<T, K, F> {
+    /// Keeps the `k` values with the largest `key(value)`.
+    pub fn by_key(k: usize, key: F) -> Self {
+        TopK {
+            k,
+            key,
+            inserted: 0,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn push(&mut self, value: T) {
+        let key = (self.key)(&value);
+        self.insert(key, value);
+    }
+
+    /// Keeps `value` if there is room or its key beats the smallest kept one.
+    fn insert(&mut self, key: K, value: T) {
+        let entry = Keyed {
+            key,
+            order: self.inserted,
+            value,
+        };
+        self.inserted += 1;
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(entry));
+        } else if let Some(mut smallest) = self.heap.peek_mut() {
+            if entry > smallest.0 {
+                *smallest = Reverse(entry);
+            }
+        }
+    }
+
+    /// Smallest of the kept values, the one the next push would compete against.
+    pub fn peek_min(&self) -> Option<&T> {
+        self.heap.peek().map(|entry| &entry.0.value)
+    }
+
+    /// Adds the values kept by another accumulator, reusing their keys, as if they were
+    /// pushed here after the current ones in the order the other accumulator saw them.
+    pub fn merge<G>(&mut self, other: TopK<T, K, G>) {
+        let mut entries = other.heap.into_vec();
+        entries.sort_unstable_by_key(|entry| entry.0.order);
+        for Reverse(entry) in entries {
+            self.insert(entry.key, entry.value);
+        }
+    }
+
+    /// Kept values from the largest key to the smallest.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        // ascending order of Reverse is descending order of the keys
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|entry| entry.0.value)
+            .collect()
+    }
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> Extend<T> for TopK<T, K, F> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+/// Top-k accumulator for a small `K` known at compile time, kept sorted in a fixed array
+/// without allocating. Each value costs `O(K)`, which beats the heap for a handful of values.
+pub struct SmallTopK<T, const K: usize> {
+    values: [Option<T>; K],
+    len: usize,
+}
+
+impl<T: Ord, const K: usize> SmallTopK<T, K> {
+    pub fn new() -> Self {
+        SmallTopK {
+            values: std::array::from_fn(|_| None),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, value: T) {
+        // first position holding a strictly smaller value, so ties keep the earlier ones
+        let position = self.values[..self.len]
+            .iter()
+            .position(|kept| kept.as_ref().is_some_and(|kept| *kept < value))
+            .unwrap_or(self.len);
+        if position == K {
+            return;
+        }
+        if self.len < K {
+            self.len += 1;
+        }
+        self.values[position..self.len].rotate_right(1);
+        self.values[position] = Some(value);
+    }
+
+    pub fn merge(&mut self, other: SmallTopK<T, K>) {
+        self.extend(IntoIterator::into_iter(other.values).flatten());
+    }
+
+    /// Kept values from the largest to the smallest.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.values[..self.len].iter().flatten()
+    }
+}
+
+impl<T: Ord, const K: usize> Default for SmallTopK<T, K> {
+    fn default() -> Self {
+        SmallTopK::new()
+    }
+}
+
+impl<T: Ord, const K: usize> Extend<T> for SmallTopK<T, K> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pairwise_product::pairwise_product;
+    use crate::top_k::{SmallTopK, TopK};
+    use rand::RngCore;
+
+    fn naive_top_k(values: &[u32], k: usize) -> Vec<u32> {
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+        sorted.truncate(k);
+        sorted
+    }
+
+    #[test]
+    fn top_k_example() {
+        let mut top = TopK::new(3);
+        top.extend(vec![5, 1, 9, 3, 7, 9]);
+        assert_eq!(top.len(), 3);
+        assert_eq!(top.peek_min(), Some(&7));
+        assert_eq!(top.into_sorted_vec(), vec![9, 9, 7]);
+
+        let mut empty: TopK<u8, u8, _> = TopK::new(0);
+        empty.push(1);
+        assert!(empty.is_empty());
+        assert_eq!(empty.peek_min(), None);
+    }
+
+    #[test]
+    fn top_k_by_key_keeps_first_on_ties() {
+        let mut top = TopK::by_key(2, |word: &&str| word.len());
+        top.extend(vec!["ab", "cde", "fg", "hij", "k", "lmn"]);
+        let mut kept = top.into_sorted_vec();
+        kept.sort_unstable();
+        assert_eq!(kept, vec!["cde", "hij"]);
+    }
+
+    #[test]
+    fn top_k_evicts_later_ties() {
+        let mut top = TopK::by_key(2, |word: &&str| word.len());
+        top.extend(vec!["ab", "cd", "efg"]);
+        assert_eq!(top.into_sorted_vec(), vec!["efg", "ab"]);
+
+        let mut top = TopK::by_key(2, |word: &&str| word.len());
+        top.push("ab");
+        let mut other = TopK::by_key(2, |word: &&str| word.len());
+        other.extend(vec!["cd", "ef", "ghi"]);
+        top.merge(other);
+        assert_eq!(top.into_sorted_vec(), vec!["ghi", "ab"]);
+    }
+
+    #[test]
+    fn top_k_merge_matches_single_pass() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let k = rng.next_u32() as usize % 10;
+            let values: Vec<u32> = (0..100).map(|_| rng.next_u32() % 50).collect();
+            let (left, right) = values.split_at(rng.next_u32() as usize % 100);
+            let mut top = TopK::new(k);
+            top.extend(left.iter().cloned());
+            let mut other = TopK::new(k);
+            other.extend(right.iter().cloned());
+            top.merge(other);
+            assert_eq!(top.into_sorted_vec(), naive_top_k(&values, k));
+        }
+    }
+
+    #[test]
+    fn small_top_k_matches_naive() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let values: Vec<u32> = (0..50).map(|_| rng.next_u32() % 20).collect();
+            let mut small: SmallTopK<u32, 4> = SmallTopK::new();
+            small.extend(values[..25].iter().cloned());
+            let mut other = SmallTopK::new();
+            other.extend(values[25..].iter().cloned());
+            small.merge(other);
+            assert_eq!(
+                small.iter().cloned().collect::<Vec<u32>>(),
+                naive_top_k(&values, 4)
+            );
+        }
+        let mut small: SmallTopK<u8, 3> = SmallTopK::default();
+        small.push(2);
+        assert_eq!(small.len(), 1);
+        assert_eq!(small.iter().collect::<Vec<&u8>>(), vec![&2]);
+    }
+
+    #[test]
+    fn small_top_k_pairwise_product() {
+        let mut rng = rand::thread_rng();
+        let values: Vec<u64> = (0..100).map(|_| rng.next_u32() as u64).collect();
+        let mut top: SmallTopK<u64, 2> = SmallTopK::new();
+        top.extend(values.iter().cloned());
+        assert_eq!(top.iter().product::<u64>(), pairwise_product(&values));
+    }
+}